@@ -1,12 +1,14 @@
 use crate::error::ContractError;
+use crate::migrate::{migrate_storage, save_contract_version};
 use crate::msg::{
     BurnInfoQueryMsg, BurnInfoResponse, ContractInfo, ContractsWithInfoResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg,
+    InstantiateMsg, MigrateMsg, QueryMsg, Role, RolesResponse,
 };
+use crate::roles::{check_role, grant_role, revoke_role};
 use crate::state::{State, CONFIG_ITEM};
 use cosmwasm_std::{
-    entry_point, to_binary, Addr, Binary, Deps, DepsMut,
-    Env, MessageInfo, Response, StdError, StdResult,
+    entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult,
 };
 use secret_toolkit::utils::Query;
 
@@ -25,10 +27,19 @@ pub fn instantiate(
         owner: info.sender.clone(),
         burn_contracts: msg.burn_contracts,
         is_active: true,
+        pending_owner: None,
+        roles: vec![],
     };
 
     //Save Contract state
     CONFIG_ITEM.save(deps.storage, &state)?;
+    save_contract_version(deps.storage)?;
+    Ok(Response::default())
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_storage(deps.storage)?;
     Ok(Response::default())
 }
 
@@ -47,6 +58,14 @@ pub fn execute(
         ExecuteMsg::SetActiveState { is_active } => {
             try_set_active_state(deps, _env, &info.sender, is_active)
         }
+        ExecuteMsg::GrantRole { role, address } => {
+            try_grant_role(deps, &info.sender, role, address)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            try_revoke_role(deps, &info.sender, role, address)
+        }
+        ExecuteMsg::ProposeOwner { new_owner } => try_propose_owner(deps, &info.sender, new_owner),
+        ExecuteMsg::AcceptOwnership {} => try_accept_ownership(deps, &info.sender),
     }
 }
 
@@ -57,19 +76,15 @@ fn try_add_contract(
 ) -> Result<Response, ContractError> {
     let mut state = CONFIG_ITEM.load(deps.storage)?;
 
-    if sender.clone() != state.owner {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
-        });
-    }
+    check_role(&state, sender, Role::Operator)?;
 
     let position = state
         .burn_contracts
         .iter()
         .position(|x| x.address == contract.address);
     if position.is_some() {
-        return Err(ContractError::CustomError {
-            val: "Contract already exists".to_string(),
+        return Err(ContractError::ContractExists {
+            address: contract.address.to_string(),
         });
     }
 
@@ -85,19 +100,15 @@ fn try_remove_contract(
 ) -> Result<Response, ContractError> {
     let mut state = CONFIG_ITEM.load(deps.storage)?;
 
-    if sender.clone() != state.owner {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
-        });
-    }
+    check_role(&state, sender, Role::Operator)?;
     let position = state
         .burn_contracts
         .iter()
         .position(|x| x.address == contract.address);
 
     if position.is_none() {
-        return Err(ContractError::CustomError {
-            val: "Contract doesn't exist".to_string(),
+        return Err(ContractError::ContractNotFound {
+            address: contract.address.to_string(),
         });
     } else {
         state.burn_contracts.remove(position.unwrap());
@@ -114,6 +125,7 @@ pub fn try_set_active_state(
 ) -> Result<Response, ContractError> {
     let mut state = CONFIG_ITEM.load(deps.storage)?;
 
+    check_role(&state, sender, Role::Pauser)?;
     state.is_active = is_active;
 
     CONFIG_ITEM.save(deps.storage, &state)?;
@@ -121,14 +133,87 @@ pub fn try_set_active_state(
     Ok(Response::default())
 }
 
+pub fn try_grant_role(
+    deps: DepsMut,
+    sender: &Addr,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    if sender.clone() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    grant_role(&mut state.roles, role, address);
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_revoke_role(
+    deps: DepsMut,
+    sender: &Addr,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    if sender.clone() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    revoke_role(&mut state.roles, role, &address)?;
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_propose_owner(
+    deps: DepsMut,
+    sender: &Addr,
+    new_owner: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    if sender.clone() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    state.pending_owner = new_owner;
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_accept_ownership(deps: DepsMut, sender: &Addr) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    match state.pending_owner.take() {
+        Some(pending_owner) if &pending_owner == sender => state.owner = pending_owner,
+        Some(_) => return Err(ContractError::Unauthorized {}),
+        None => return Err(ContractError::NoPendingOwner {}),
+    }
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
 #[entry_point]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetContracts {} => to_binary(&query_contracts(deps)?),
         QueryMsg::GetContractsWithInfo {} => to_binary(&query_contracts_info(deps)?),
+        QueryMsg::GetRoles {} => to_binary(&query_roles(deps)?),
     }
 }
 
+fn query_roles(deps: Deps) -> StdResult<RolesResponse> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    Ok(RolesResponse {
+        owner: state.owner,
+        pending_owner: state.pending_owner,
+        roles: state.roles,
+    })
+}
+
 fn query_contracts(deps: Deps) -> StdResult<Vec<ContractInfo>> {
     let state = CONFIG_ITEM.load(deps.storage)?;
     Ok(state.burn_contracts)
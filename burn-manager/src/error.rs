@@ -1,18 +1,83 @@
+use crate::msg::Role;
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
+/// Codes are shared with the burn contract, every message starts with its
+/// code so clients can match on it
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     // let thiserror implement From<StdError> for you
     Std(#[from] StdError),
 
-    #[error("Unauthorized")]
+    #[error("E1001: unauthorized")]
     // issued when message sender != owner
     Unauthorized {},
 
-    #[error("Custom Error val: {val:?}")]
-    CustomError { val: String },
-    // Add any other custom errors you like here.
-    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+    #[error("E1006: {address} does not hold the {role:?} role")]
+    RoleNotHeld { role: Role, address: String },
+
+    #[error("E1007: no ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("E5002: contract {address} already exists")]
+    ContractExists { address: String },
+
+    #[error("E5003: contract {address} doesn't exist")]
+    ContractNotFound { address: String },
+}
+
+impl ContractError {
+    /// Stable code of the error, `None` for errors of the framework
+    pub fn code(&self) -> Option<u32> {
+        let code = match self {
+            ContractError::Std(_) => return None,
+            ContractError::Unauthorized {} => 1001,
+            ContractError::RoleNotHeld { .. } => 1006,
+            ContractError::NoPendingOwner {} => 1007,
+            ContractError::ContractExists { .. } => 5002,
+            ContractError::ContractNotFound { .. } => 5003,
+        };
+        Some(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// one of every variant but `Std`, which has no code
+    fn every_error() -> Vec<ContractError> {
+        vec![
+            ContractError::Unauthorized {},
+            ContractError::RoleNotHeld {
+                role: Role::Operator,
+                address: "x".to_string(),
+            },
+            ContractError::NoPendingOwner {},
+            ContractError::ContractExists {
+                address: "x".to_string(),
+            },
+            ContractError::ContractNotFound {
+                address: "x".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn messages_start_with_code() {
+        let errors = every_error();
+        let mut codes: Vec<u32> = Vec::new();
+        for error in errors.iter() {
+            let code = error.code().unwrap();
+            let prefix = format!("E{}: ", code);
+            assert!(error.to_string().starts_with(&prefix), "{}", error);
+            assert!(!codes.contains(&code), "{} is used twice", code);
+            codes.push(code);
+        }
+        assert_eq!(
+            ContractError::Std(StdError::generic_err("failed")).code(),
+            None
+        );
+    }
 }
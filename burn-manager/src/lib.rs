@@ -1,5 +1,7 @@
 pub mod contract;
 mod error;
+mod migrate;
 pub mod msg;
+mod roles;
 pub mod state;
 pub use crate::error::ContractError;
@@ -0,0 +1,89 @@
+use crate::msg::ContractInfo;
+use crate::state::{ContractVersion, State, CONFIG_ITEM, CONFIG_KEY, CONTRACT_VERSION_ITEM};
+use cosmwasm_std::{Addr, StdResult, Storage};
+use schemars::JsonSchema;
+use secret_toolkit::storage::Item;
+use serde::{Deserialize, Serialize};
+
+pub const CONTRACT_NAME: &str = "burn-manager";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// layout of `State`, bumped with every layout change
+pub const STATE_VERSION: u32 = 2;
+
+/// Layout of the contracts deployed before the version record
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct StateV1 {
+    pub owner: Addr,
+    pub is_active: bool,
+    pub burn_contracts: Vec<ContractInfo>,
+}
+
+static CONFIG_ITEM_V1: Item<StateV1> = Item::new(CONFIG_KEY);
+
+pub fn state_from_v1(old: StateV1) -> State {
+    State {
+        owner: old.owner,
+        is_active: old.is_active,
+        burn_contracts: old.burn_contracts,
+        pending_owner: None,
+        roles: vec![],
+    }
+}
+
+/// Brings the stored layout up to `STATE_VERSION`, contracts without a version
+/// record are on the v1 layout
+pub fn migrate_storage(storage: &mut dyn Storage) -> StdResult<()> {
+    let state_version = match CONTRACT_VERSION_ITEM.may_load(storage)? {
+        Some(version) => version.state_version,
+        None => 1,
+    };
+    if state_version < 2 {
+        let old = CONFIG_ITEM_V1.load(storage)?;
+        CONFIG_ITEM.save(storage, &state_from_v1(old))?;
+    }
+    save_contract_version(storage)
+}
+
+pub fn save_contract_version(storage: &mut dyn Storage) -> StdResult<()> {
+    CONTRACT_VERSION_ITEM.save(
+        storage,
+        &ContractVersion {
+            contract: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
+            state_version: STATE_VERSION,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::migrate;
+    use crate::msg::MigrateMsg;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+
+    #[test]
+    fn v1_state_migrates() {
+        let mut deps = mock_dependencies();
+        let old = StateV1 {
+            owner: Addr::unchecked("owner"),
+            is_active: false,
+            burn_contracts: vec![ContractInfo {
+                code_hash: "hash".to_string(),
+                address: Addr::unchecked("burn"),
+                name: "burn".to_string(),
+            }],
+        };
+        CONFIG_ITEM_V1.save(&mut deps.storage, &old).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let state = CONFIG_ITEM.load(&deps.storage).unwrap();
+        assert_eq!(state, state_from_v1(old));
+        let version = CONTRACT_VERSION_ITEM.load(&deps.storage).unwrap();
+        assert_eq!(version.state_version, STATE_VERSION);
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(CONFIG_ITEM.load(&deps.storage).unwrap(), state);
+    }
+}
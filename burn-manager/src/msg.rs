@@ -1,12 +1,15 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
 use schemars::JsonSchema;
-use secret_toolkit::utils::Query;
+use secret_toolkit::{snip721::Metadata, utils::Query};
 use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct InstantiateMsg {
     pub burn_contracts: Vec<ContractInfo>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct ContractInfo {
     pub code_hash: String,
@@ -21,16 +24,127 @@ pub struct RewardsContractInfo {
     pub base_reward: Uint128,
     pub bonus_hourly: Uint128,
     pub name: String,
-    pub burn_type: String,
+    pub burn_type: RewardStrategy,
     pub total_rewards: Uint128,
+    pub bonus_curve: Option<BonusCurve>,
+    pub bonus_reset: Option<BonusReset>,
+    pub vesting: Option<VestingSchedule>,
+    pub lottery: Option<Vec<LotteryPrize>>,
+    pub denom: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LotteryPrize {
+    pub weight: u32,
+    pub outcome: LotteryOutcome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LotteryOutcome {
+    Multiplier { multiplier: Decimal },
+    Jackpot { amount: Uint128 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingSchedule {
+    pub cliff_seconds: u64,
+    pub duration_seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardStrategy {
+    Flat,
+    Rank,
+    TraitWeighted { weights: Vec<TraitWeight> },
+    BurnCountTiers { tiers: Vec<BurnCountTier> },
+    Curve { curve: BurnCurve },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TraitWeight {
+    pub trait_type: String,
+    pub value: String,
+    pub effect: TraitEffect,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitEffect {
+    Add { amount: Uint128 },
+    Multiply { multiplier: Decimal },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnCountTier {
+    pub min_burned: u32,
+    pub reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnCurve {
+    Linear { decrement: Uint128, floor: Uint128 },
+    Halving { epoch_burns: u32, floor: Uint128 },
+    Piecewise { points: Vec<CurvePoint> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CurvePoint {
+    pub burned: u32,
+    pub reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BonusCurve {
+    LinearCapped { cap: Uint128 },
+    Step { steps: Vec<BonusStep> },
+    Exponential { max: Uint128, half_life_hours: u64 },
+    DutchAuction { period_hours: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BonusReset {
+    OnBonusPayout,
+    OnAnyPayout,
+    Manual,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BonusStep {
+    pub after_hours: u64,
+    pub bonus: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    AddContract { contract: ContractInfo },
-    RemoveContract { contract: ContractInfo },
-    SetActiveState { is_active: bool },
+    AddContract {
+        contract: ContractInfo,
+    },
+    RemoveContract {
+        contract: ContractInfo,
+    },
+    SetActiveState {
+        is_active: bool,
+    },
+    GrantRole {
+        role: Role,
+        address: Addr,
+    },
+    RevokeRole {
+        role: Role,
+        address: Addr,
+    },
+    /// starts a transfer of ownership, `None` cancels a pending one
+    ProposeOwner {
+        new_owner: Option<Addr>,
+    },
+    /// completes the transfer, sent by the proposed owner
+    AcceptOwnership {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -38,6 +152,30 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     GetContracts {},
     GetContractsWithInfo {},
+    GetRoles {},
+}
+
+/// Roles held next to the owner, who holds all of them
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// adding and removing burn contracts
+    Operator,
+    /// pausing and resuming the manager
+    Pauser,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RoleGrant {
+    pub role: Role,
+    pub address: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RolesResponse {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub roles: Vec<RoleGrant>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -49,11 +187,122 @@ pub struct ContractsWithInfoResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct BurnInfoResponse {
     pub total_burned_amount: u32,
-    pub nft_contract: ContractInfo,
+    pub nft_contracts: Vec<CollectionBurnInfo>,
     pub reward_contracts: Vec<RewardsContractInfo>,
-    pub trait_restriction: Option<String>,
+    pub trait_rules: Option<TraitRule>,
     pub is_active: bool,
     pub burn_counter_date: u64,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub status: CampaignStatus,
+    pub mint_reward: Option<MintReward>,
+    pub minted_amount: u32,
+    pub recipes: Vec<Recipe>,
+    pub lottery_commitment: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Recipe {
+    pub name: String,
+    pub ingredients: Vec<RecipeIngredient>,
+    pub rewards: Vec<RecipeReward>,
+    pub replaces_token_rewards: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeIngredient {
+    Matching { rule: TraitRule, count: u32 },
+    SameTrait { trait_type: String, count: u32 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RecipeReward {
+    pub reward_contract_name: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MintReward {
+    pub contract: ContractInfo,
+    pub burn_ratio: u32,
+    pub token_id_prefix: String,
+    pub public_metadata: Metadata,
+    pub private_metadata: Option<Metadata>,
+    pub carry_traits: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignStatus {
+    Scheduled,
+    Running,
+    Ended,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitRule {
+    HasTrait {
+        trait_type: String,
+    },
+    Equals {
+        trait_type: String,
+        value: String,
+    },
+    OneOf {
+        trait_type: String,
+        values: Vec<String>,
+    },
+    And {
+        rules: Vec<TraitRule>,
+    },
+    Or {
+        rules: Vec<TraitRule>,
+    },
+    Not {
+        rule: Box<TraitRule>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CollectionBurnInfo {
+    pub collection: NftCollection,
+    pub total_burned_amount: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NftCollection {
+    pub contract: ContractInfo,
+    pub trait_rules: Option<TraitRule>,
+    pub reward_multipliers: Vec<RewardMultiplier>,
+    pub upgrade: Option<UpgradeConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct UpgradeConfig {
+    pub burn_count: u32,
+    pub rules: Vec<UpgradeRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeRule {
+    IncrementTrait {
+        trait_type: String,
+        amount: u32,
+        max: Option<u32>,
+    },
+    SetTrait {
+        trait_type: String,
+        value: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RewardMultiplier {
+    pub reward_contract_name: String,
+    pub multiplier: Decimal,
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,85 @@
+use crate::error::ContractError;
+use crate::msg::{Role, RoleGrant};
+use crate::state::State;
+use cosmwasm_std::Addr;
+
+/// The owner holds every role
+pub fn has_role(state: &State, sender: &Addr, role: Role) -> bool {
+    sender == &state.owner
+        || state
+            .roles
+            .iter()
+            .any(|x| x.role == role && &x.address == sender)
+}
+
+pub fn check_role(state: &State, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if !has_role(state, sender, role) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn grant_role(roles: &mut Vec<RoleGrant>, role: Role, address: Addr) {
+    if !roles.iter().any(|x| x.role == role && x.address == address) {
+        roles.push(RoleGrant { role, address });
+    }
+}
+
+pub fn revoke_role(
+    roles: &mut Vec<RoleGrant>,
+    role: Role,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    let position = roles
+        .iter()
+        .position(|x| x.role == role && &x.address == address)
+        .ok_or_else(|| ContractError::RoleNotHeld {
+            role,
+            address: address.to_string(),
+        })?;
+    roles.remove(position);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::instantiate;
+    use crate::msg::InstantiateMsg;
+    use crate::state::CONFIG_ITEM;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+
+    #[test]
+    fn only_pausers_pause() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            InstantiateMsg {
+                burn_contracts: vec![],
+            },
+        )
+        .unwrap();
+        let mut state = CONFIG_ITEM.load(&deps.storage).unwrap();
+        let pauser = Addr::unchecked("pauser");
+
+        assert_eq!(
+            check_role(&state, &pauser, Role::Pauser),
+            Err(ContractError::Unauthorized {})
+        );
+        grant_role(&mut state.roles, Role::Pauser, pauser.clone());
+        assert!(check_role(&state, &pauser, Role::Pauser).is_ok());
+        assert!(check_role(&state, &pauser, Role::Operator).is_err());
+        assert!(check_role(&state, &state.owner, Role::Operator).is_ok());
+
+        revoke_role(&mut state.roles, Role::Pauser, &pauser).unwrap();
+        assert_eq!(
+            revoke_role(&mut state.roles, Role::Pauser, &pauser),
+            Err(ContractError::RoleNotHeld {
+                role: Role::Pauser,
+                address: "pauser".to_string(),
+            })
+        );
+    }
+}
@@ -1,18 +1,30 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::ContractInfo;
-use cosmwasm_std::{Addr};
-use secret_toolkit::{ 
-    storage::{Item},
-};
+use crate::msg::{ContractInfo, RoleGrant};
+use cosmwasm_std::Addr;
+use secret_toolkit::storage::Item;
 
 pub static CONFIG_KEY: &[u8] = b"config";
 pub static CONFIG_ITEM: Item<State> = Item::new(CONFIG_KEY);
+pub static CONTRACT_VERSION_KEY: &[u8] = b"contract_version";
+pub static CONTRACT_VERSION_ITEM: Item<ContractVersion> = Item::new(CONTRACT_VERSION_KEY);
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct State {
     pub owner: Addr,
     pub is_active: bool,
     pub burn_contracts: Vec<ContractInfo>,
+    /// owner proposed by `ProposeOwner`, waiting for `AcceptOwnership`
+    pub pending_owner: Option<Addr>,
+    pub roles: Vec<RoleGrant>,
+}
+
+/// Written by instantiate and every migration, `state_version` is the layout
+/// of the stored state
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ContractVersion {
+    pub contract: String,
+    pub version: String,
+    pub state_version: u32,
 }
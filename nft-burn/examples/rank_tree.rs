@@ -0,0 +1,69 @@
+//! Builds the rank merkle tree from a csv and writes the proof of every token
+//!
+//! ```text
+//! cargo run --example rank_tree -- secret1collection ranks.csv proofs.json
+//! ```
+//!
+//! Every csv line is `token_id,rank`, a first line starting with `token_id` is
+//! skipped. Leaves include the collection address, so the
+//! proofs only hold for burns of that collection. The printed root goes into
+//! `SetRankRoot` and the proofs of burned tokens into `ClaimBurnRewards`.
+
+use cosmwasm_std::{Addr, Binary};
+use nft_stake::merkle::{build_tree, leaf_hash};
+use nft_stake::msg::{Rank, RankProof};
+use std::{env, fs, process};
+
+fn parse_line(line: &str) -> Result<Rank, String> {
+    let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+    if fields.len() != 2 {
+        return Err(format!("expected 2 fields in `{}`", line));
+    }
+    let rank: u32 = fields[1]
+        .parse()
+        .map_err(|_| format!("rank of `{}` is not a number", line))?;
+    Ok(Rank {
+        token_id: fields[0].to_string(),
+        rank,
+    })
+}
+
+fn run(collection: &str, input: &str, output: &str) -> Result<(), String> {
+    let collection = Addr::unchecked(collection);
+    let csv = fs::read_to_string(input).map_err(|e| e.to_string())?;
+    let ranks = csv
+        .lines()
+        .filter(|x| !x.trim().is_empty() && !x.starts_with("token_id"))
+        .map(parse_line)
+        .collect::<Result<Vec<Rank>, String>>()?;
+
+    let leaves: Vec<[u8; 32]> = ranks.iter().map(|x| leaf_hash(&collection, x)).collect();
+    let (root, proofs) = build_tree(&leaves);
+    let rank_proofs: Vec<RankProof> = ranks
+        .into_iter()
+        .zip(proofs)
+        .map(|(rank, proof)| RankProof { rank, proof })
+        .collect();
+
+    let json = serde_json::to_string(&rank_proofs).map_err(|e| e.to_string())?;
+    fs::write(output, json).map_err(|e| e.to_string())?;
+    println!("root: {}", Binary::from(root.to_vec()).to_base64());
+    println!(
+        "proofs of {} tokens written to {}",
+        rank_proofs.len(),
+        output
+    );
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 4 {
+        eprintln!("usage: rank_tree <collection> <ranks.csv> <proofs.json>");
+        process::exit(1);
+    }
+    if let Err(e) = run(&args[1], &args[2], &args[3]) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
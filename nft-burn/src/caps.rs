@@ -0,0 +1,192 @@
+use crate::error::ContractError;
+use crate::msg::{BurnAllowanceResponse, BurnCaps};
+use crate::state::{TimedBurn, WalletBurns};
+
+/// Drops the wallet's burns that left the period window, returns the burns
+/// still in it
+fn prune_period(caps: &BurnCaps, wallet: &mut WalletBurns, current_time: u64) -> u32 {
+    match caps.max_per_period.as_ref() {
+        Some(period) => {
+            wallet
+                .recent
+                .retain(|x| current_time < x.time.saturating_add(period.period_seconds));
+            wallet.recent.iter().map(|x| x.count).sum()
+        }
+        None => {
+            wallet.recent.clear();
+            0
+        }
+    }
+}
+
+/// Checks every cap for a burn of `count` tokens and records it on the wallet
+pub fn check_burn_caps(
+    caps: &BurnCaps,
+    wallet: &mut WalletBurns,
+    total_burned: u32,
+    count: u32,
+    current_time: u64,
+) -> Result<(), ContractError> {
+    if let Some(max) = caps.max_per_tx {
+        if count > max {
+            return Err(ContractError::TxCapExceeded { max });
+        }
+    }
+    if let Some(max) = caps.max_total {
+        if total_burned.saturating_add(count) > max {
+            return Err(ContractError::CampaignCapReached {
+                remaining: max.saturating_sub(total_burned),
+            });
+        }
+    }
+    if let Some(max) = caps.max_per_wallet {
+        if wallet.total.saturating_add(count) > max {
+            return Err(ContractError::WalletCapReached {
+                remaining: max.saturating_sub(wallet.total),
+            });
+        }
+    }
+    let mut pruned = wallet.clone();
+    let period_burns = prune_period(caps, &mut pruned, current_time);
+    if let Some(period) = caps.max_per_period.as_ref() {
+        if period_burns.saturating_add(count) > period.max_burns {
+            return Err(ContractError::PeriodCapReached {
+                remaining: period.max_burns.saturating_sub(period_burns),
+                resets_at: pruned
+                    .recent
+                    .first()
+                    .map_or(current_time, |x| x.time + period.period_seconds),
+            });
+        }
+        match pruned.recent.last_mut() {
+            Some(last) if last.time == current_time => last.count += count,
+            _ => pruned.recent.push(TimedBurn {
+                time: current_time,
+                count,
+            }),
+        }
+    }
+
+    pruned.total += count;
+    *wallet = pruned;
+    Ok(())
+}
+
+pub fn burn_allowance(
+    caps: &BurnCaps,
+    mut wallet: WalletBurns,
+    total_burned: u32,
+    current_time: u64,
+) -> BurnAllowanceResponse {
+    let period_burns = prune_period(caps, &mut wallet, current_time);
+    let period = caps.max_per_period.as_ref();
+    BurnAllowanceResponse {
+        wallet_burned: wallet.total,
+        max_per_tx: caps.max_per_tx,
+        remaining_wallet: caps.max_per_wallet.map(|x| x.saturating_sub(wallet.total)),
+        remaining_period: period.map(|x| x.max_burns.saturating_sub(period_burns)),
+        period_resets_at: period
+            .zip(wallet.recent.first())
+            .map(|(x, oldest)| oldest.time + x.period_seconds),
+        remaining_total: caps.max_total.map(|x| x.saturating_sub(total_burned)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::PeriodCap;
+
+    #[test]
+    fn caps_limit_wallet_and_period() {
+        let caps = BurnCaps {
+            max_per_tx: Some(5),
+            max_per_wallet: Some(8),
+            max_per_period: Some(PeriodCap {
+                max_burns: 4,
+                period_seconds: 100,
+            }),
+            max_total: Some(10),
+        };
+        let mut wallet = WalletBurns::default();
+
+        assert_eq!(
+            check_burn_caps(&caps, &mut wallet, 0, 6, 1000),
+            Err(ContractError::TxCapExceeded { max: 5 })
+        );
+        check_burn_caps(&caps, &mut wallet, 0, 3, 1000).unwrap();
+        assert_eq!(
+            check_burn_caps(&caps, &mut wallet, 3, 2, 1050),
+            Err(ContractError::PeriodCapReached {
+                remaining: 1,
+                resets_at: 1100,
+            })
+        );
+        check_burn_caps(&caps, &mut wallet, 3, 1, 1050).unwrap();
+
+        let allowance = burn_allowance(&caps, wallet.clone(), 4, 1099);
+        assert_eq!(allowance.remaining_period, Some(0));
+        assert_eq!(allowance.period_resets_at, Some(1100));
+        assert_eq!(allowance.remaining_wallet, Some(4));
+
+        // the burn at 1050 is still in the window
+        assert_eq!(
+            check_burn_caps(&caps, &mut wallet, 4, 4, 1100),
+            Err(ContractError::PeriodCapReached {
+                remaining: 3,
+                resets_at: 1150,
+            })
+        );
+        check_burn_caps(&caps, &mut wallet, 4, 3, 1100).unwrap();
+        assert_eq!(wallet.total, 7);
+        assert_eq!(
+            check_burn_caps(&caps, &mut wallet, 7, 2, 1300),
+            Err(ContractError::WalletCapReached { remaining: 1 })
+        );
+
+        let mut other = WalletBurns::default();
+        assert_eq!(
+            check_burn_caps(&caps, &mut other, 8, 3, 1300),
+            Err(ContractError::CampaignCapReached { remaining: 2 })
+        );
+        // a failed burn leaves the wallet untouched
+        assert_eq!(other, WalletBurns::default());
+    }
+
+    #[test]
+    fn period_cap_rolls_across_boundaries() {
+        let caps = BurnCaps {
+            max_per_period: Some(PeriodCap {
+                max_burns: 4,
+                period_seconds: 100,
+            }),
+            ..BurnCaps::default()
+        };
+        let mut wallet = WalletBurns::default();
+
+        check_burn_caps(&caps, &mut wallet, 0, 4, 1099).unwrap();
+        assert_eq!(
+            check_burn_caps(&caps, &mut wallet, 4, 1, 1101),
+            Err(ContractError::PeriodCapReached {
+                remaining: 0,
+                resets_at: 1199,
+            })
+        );
+        check_burn_caps(&caps, &mut wallet, 4, 2, 1199).unwrap();
+        check_burn_caps(&caps, &mut wallet, 6, 2, 1199).unwrap();
+        assert_eq!(
+            wallet.recent,
+            vec![TimedBurn {
+                time: 1199,
+                count: 4
+            }]
+        );
+
+        let allowance = burn_allowance(&caps, wallet.clone(), 8, 1250);
+        assert_eq!(allowance.remaining_period, Some(0));
+        assert_eq!(allowance.period_resets_at, Some(1299));
+        let allowance = burn_allowance(&caps, wallet, 8, 1299);
+        assert_eq!(allowance.remaining_period, Some(4));
+        assert_eq!(allowance.period_resets_at, None);
+    }
+}
@@ -1,25 +1,59 @@
+use crate::caps::{burn_allowance, check_burn_caps};
 use crate::error::ContractError;
+use crate::lottery::{
+    highest_prize, lottery_entropy, lowest_prize, next_roll, prize_index, prize_reward,
+    seed_commitment,
+};
+use crate::merkle::verify_rank;
+use crate::migrate::{
+    full_history, full_history_len, migrate_storage, save_contract_version, user_history,
+    user_history_len, v1_nft_contract, v1_rank_store,
+};
+use crate::mint::{
+    add_minted, minted_amount, minted_count, minted_metadata, minted_token_id, validate_mint_reward,
+};
 use crate::msg::{
-    BurnInfoResponse, ExecuteMsg, ExpectedReward, ExpectedRewardResponse, HandleNftReceiveMsg,
-    HandleReceiveMsg, History, HistoryFull, InstantiateMsg, QueryMsg, Rank, Reward,
-    RewardsContractInfo,
+    BonusReset, BurnAllowanceResponse, BurnCaps, BurnInfoResponse, CampaignStatus, ClaimDeadline,
+    ClaimPayout, CollectionBurnInfo, ContractInfo, ExecuteMsg, ExpectedCheck, ExpectedReward,
+    ExpectedRewardResponse, HandleNftReceiveMsg, HandleReceiveMsg, History, HistoryFull,
+    InstantiateMsg, LotteryRange, LotteryRoll, MigrateMsg, MintReward, NftCollection, QueryMsg,
+    Rank, RankProof, RankResponse, RankTier, RankTiersResponse, RanksResponse, RarityMethod,
+    RarityPhase, RarityStatus, RarityStatusResponse, Recipe, RecipeMatchResponse, Reward,
+    RewardStrategy, RewardsContractInfo, Role, RolesResponse, ScarcityBonus, ScarcityResponse,
+    SimulateClaimResponse, TokenMinimum, TokenUpgrade, TraitCheckResponse, TraitRule,
+    TraitScarcity, TraitWeight, VestingBalance,
 };
-use crate::rand::sha_256;
+use crate::rand::{sha_256, Prng};
+use crate::ranks::{decode_ranks, validate_rank_tiers, RankStore};
+use crate::rarity::{competition_rank, ranks_before, rarity_score, ScoreInput};
+use crate::recipe::{find_recipe, validate_recipes};
+use crate::roles::{check_role, grant_role, revoke_role};
+use crate::scarcity::{scarcity_multiplier, supply_multiplier, validate_scarcity_bonus};
 use crate::state::{
-    State, ADMIN_VIEWING_KEY_ITEM, BURN_HISTORY_STORE, CONFIG_ITEM, HISTORY_STORE,
-    PREFIX_REVOKED_PERMITS, RANK_STORE,
+    RarityScore, RarityToken, State, VestingEntry, WalletBurns, ADMIN_VIEWING_KEY_ITEM,
+    BONUS_CLOCKS_STORE, BURN_HISTORY_STORE, COLLECTION_BURNS_STORE, CONFIG_ITEM, HISTORY_STORE,
+    PREFIX_REVOKED_PERMITS, RANK_GENERATIONS_STORE, RANK_ROOTS_STORE, RANK_STORE, RANK_TIERS_ITEM,
+    RARITY_ITEM, RARITY_SCORES_STORE, RARITY_SIZES_STORE, RARITY_TOKENS_STORE, RARITY_TYPES_STORE,
+    RARITY_VALUES_STORE, SCARCITY_STORE, TRAIT_BURNS_STORE, VESTING_STORE, WALLET_BURNS_STORE,
 };
+use crate::strategy::{accrued_bonus, strategy_reward, validate_reward_contracts, TokenContext};
+use crate::traits::token_attributes;
+use crate::upgrade::{check_upgrade_rights, upgraded_metadata, validate_upgrade};
+use crate::vesting::{add_vesting, claim_vested, vesting_balances};
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Deps,
-    DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    entry_point, from_binary, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg,
+    Deps, DepsMut, Env, MessageInfo, QuerierWrapper, Response, StdError, StdResult, Storage,
+    Uint128,
 };
 use secret_toolkit::{
     permit::{validate, Permit, RevokedPermits},
     snip20::{balance_query, set_viewing_key_msg, transfer_msg, Balance},
     snip721::{
-        batch_burn_nft_msg, nft_dossier_query, register_receive_nft_msg, Burn, NftDossier,
+        all_tokens_query, batch_burn_nft_msg, batch_mint_nft_msg, mint_nft_msg, nft_dossier_query,
+        register_receive_nft_msg, set_metadata_msg, Burn, Metadata, Mint, NftDossier, Trait,
         ViewerInfo,
     },
+    storage::AppendStore,
 };
 
 pub const BLOCK_SIZE: usize = 256;
@@ -32,7 +66,32 @@ pub fn instantiate(
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> Result<Response, StdError> {
+) -> Result<Response, ContractError> {
+    validate_reward_contracts(&msg.reward_contracts)?;
+    if let Some(trait_rules) = msg.trait_rules.as_ref() {
+        trait_rules.validate()?;
+    }
+    if msg.nft_contracts.is_empty() {
+        return Err(ContractError::NoNftContracts {});
+    }
+    for (i, collection) in msg.nft_contracts.iter().enumerate() {
+        if msg.nft_contracts[..i]
+            .iter()
+            .any(|x| x.contract.address == collection.contract.address)
+        {
+            return Err(ContractError::ContractExists {
+                address: collection.contract.address.to_string(),
+            });
+        }
+        validate_collection(collection, &msg.reward_contracts)?;
+    }
+    validate_schedule(msg.start_time, msg.end_time)?;
+    if let Some(mint_reward) = msg.mint_reward.as_ref() {
+        validate_mint_reward(mint_reward)?;
+    }
+    validate_recipes(&msg.recipes, &msg.reward_contracts)?;
+
+    let lottery_seed: Vec<u8> = sha_256(msg.entropy.as_bytes()).to_vec();
     let prng_seed: Vec<u8> = sha_256(base64::encode(msg.entropy).as_bytes()).to_vec();
     let viewing_key = base64::encode(&prng_seed);
 
@@ -40,18 +99,28 @@ pub fn instantiate(
     let state = State {
         viewing_key: Some(viewing_key),
         owner: info.sender.clone(),
-        nft_contract: msg.nft_contract,
+        nft_contracts: msg.nft_contracts,
         reward_contracts: msg.reward_contracts,
         total_burned_amount: 0,
         is_active: true,
-        trait_restriction: msg.trait_restriction,
+        trait_rules: msg.trait_rules,
         burn_counter_date: _env.block.time.seconds(),
+        start_time: msg.start_time,
+        end_time: msg.end_time,
+        caps: msg.caps.unwrap_or_default(),
+        prng_seed: lottery_seed,
+        mint_reward: msg.mint_reward,
+        recipes: msg.recipes,
+        pending_owner: None,
+        roles: vec![],
     };
 
     //Save Contract state
     CONFIG_ITEM.save(deps.storage, &state)?;
+    save_contract_version(deps.storage)?;
+    let ranks = rank_store(deps.storage, &state.nft_contracts[0].contract.address)?;
     for rank in msg.ranks.iter() {
-        RANK_STORE.insert(deps.storage, &rank.token_id, &rank)?;
+        ranks.insert(deps.storage, &rank.token_id, rank.rank)?;
     }
 
     let mut response_msgs: Vec<CosmosMsg> = Vec::new();
@@ -61,24 +130,15 @@ pub fn instantiate(
 
     let vk = state.viewing_key.unwrap();
 
-    response_msgs.push(register_receive_nft_msg(
-        _env.contract.code_hash,
-        Some(true),
-        None,
-        BLOCK_SIZE,
-        state.nft_contract.code_hash.clone(),
-        state.nft_contract.address.to_string(),
-    )?);
-
-    response_msgs.push(set_viewing_key_msg(
-        vk.to_string(),
-        None,
-        BLOCK_SIZE,
-        state.nft_contract.code_hash,
-        state.nft_contract.address.to_string(),
-    )?);
+    for collection in state.nft_contracts.iter() {
+        response_msgs.extend(register_collection_msgs(
+            &_env.contract.code_hash,
+            &vk,
+            &collection.contract,
+        )?);
+    }
 
-    for reward_contract in state.reward_contracts.iter() {
+    for reward_contract in state.reward_contracts.iter().filter(|x| x.denom.is_none()) {
         response_msgs.push(set_viewing_key_msg(
             vk.to_string(),
             None,
@@ -91,6 +151,12 @@ pub fn instantiate(
     Ok(Response::new().add_messages(response_msgs))
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_storage(deps.storage, &msg)?;
+    Ok(Response::default())
+}
+
 #[entry_point]
 pub fn execute(
     deps: DepsMut,
@@ -103,12 +169,37 @@ pub fn execute(
             try_revoke_permit(deps, &info.sender, &permit_name)
         }
         ExecuteMsg::UpdateRewardContract { contracts } => {
-            try_update_reward_contract(deps, &info.sender, contracts)
+            try_update_reward_contract(deps, _env, &info.sender, contracts)
         }
 
-        ExecuteMsg::UpdateRanks { ranks } => {
-            try_update_ranks(deps, &info.sender, ranks)
+        ExecuteMsg::UpdateRanks {
+            nft_contract,
+            ranks,
+        } => try_update_ranks(deps, &info.sender, nft_contract, ranks),
+        ExecuteMsg::RemoveRanks {
+            nft_contract,
+            token_ids,
+        } => try_remove_ranks(deps, &info.sender, nft_contract, token_ids),
+        ExecuteMsg::ClearRanks {
+            nft_contract,
+            limit,
+        } => try_clear_ranks(deps, &info.sender, nft_contract, limit),
+        ExecuteMsg::ImportRanks { nft_contract, data } => {
+            try_import_ranks(deps, &info.sender, nft_contract, data)
         }
+        ExecuteMsg::SetRankRoot { nft_contract, root } => {
+            try_set_rank_root(deps, &info.sender, nft_contract, root)
+        }
+        ExecuteMsg::SetRankTiers { tiers } => try_set_rank_tiers(deps, &info.sender, tiers),
+        ExecuteMsg::StartRarity {
+            nft_contract,
+            method,
+        } => try_start_rarity(deps, &info.sender, nft_contract, method),
+        ExecuteMsg::CrankRarity { limit } => try_crank_rarity(deps, _env, &info.sender, limit),
+        ExecuteMsg::SetScarcityBonus {
+            nft_contract,
+            bonus,
+        } => try_set_scarcity_bonus(deps, &info.sender, nft_contract, bonus),
         ExecuteMsg::RemoveRewards {} => try_remove_rewards(deps, &info.sender),
         ExecuteMsg::BatchReceiveNft {
             from,
@@ -125,9 +216,45 @@ pub fn execute(
         ExecuteMsg::SetActiveState { is_active } => {
             try_set_active_state(deps, _env, &info.sender, is_active)
         }
-        ExecuteMsg::ResetBurnCounterDate {} => {
-            try_reset_burn_counter_date(deps, _env, &info.sender)
+        ExecuteMsg::ResetBurnCounterDate {
+            reward_contract_name,
+        } => try_reset_burn_counter_date(deps, _env, &info.sender, reward_contract_name),
+        ExecuteMsg::SetTraitRules { trait_rules } => {
+            try_set_trait_rules(deps, &info.sender, trait_rules)
+        }
+        ExecuteMsg::UpdateTraitWeights {
+            reward_contract_name,
+            weights,
+        } => try_update_trait_weights(deps, &info.sender, reward_contract_name, weights),
+        ExecuteMsg::AddNftContract { collection } => {
+            try_add_nft_contract(deps, _env, &info.sender, collection)
+        }
+        ExecuteMsg::RemoveNftContract { address } => {
+            try_remove_nft_contract(deps, &info.sender, address)
+        }
+        ExecuteMsg::SetSchedule {
+            start_time,
+            end_time,
+        } => try_set_schedule(deps, &info.sender, start_time, end_time),
+        ExecuteMsg::SweepRewards { treasury } => {
+            try_sweep_rewards(deps, _env, &info.sender, treasury)
         }
+        ExecuteMsg::SetBurnCaps { caps } => try_set_burn_caps(deps, &info.sender, caps),
+        ExecuteMsg::ClaimVested {} => try_claim_vested(deps, _env, &info.sender),
+        ExecuteMsg::FundNative {} => try_fund_native(deps, &info.funds),
+        ExecuteMsg::SetMintReward { mint_reward } => {
+            try_set_mint_reward(deps, &info.sender, mint_reward)
+        }
+        ExecuteMsg::AddRecipe { recipe } => try_add_recipe(deps, &info.sender, recipe),
+        ExecuteMsg::RemoveRecipe { name } => try_remove_recipe(deps, &info.sender, name),
+        ExecuteMsg::GrantRole { role, address } => {
+            try_grant_role(deps, &info.sender, role, address)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            try_revoke_role(deps, &info.sender, role, address)
+        }
+        ExecuteMsg::ProposeOwner { new_owner } => try_propose_owner(deps, &info.sender, new_owner),
+        ExecuteMsg::AcceptOwnership {} => try_accept_ownership(deps, &info.sender),
     }
 }
 fn receive(
@@ -139,7 +266,6 @@ fn receive(
     amount: Uint128,
     msg: Option<Binary>,
 ) -> Result<Response, ContractError> {
-    deps.api.debug(&format!("Receive received"));
     let response_msgs: Vec<CosmosMsg> = Vec::new();
     let mut state = CONFIG_ITEM.load(deps.storage)?;
     if let Some(bin_msg) = msg {
@@ -148,12 +274,11 @@ fn receive(
                 let reward_contract_index = state
                     .reward_contracts
                     .iter()
-                    .position(|x| x.address == info_sender.to_string());
+                    .position(|x| x.denom.is_none() && x.address == info_sender.to_string());
 
                 if reward_contract_index.is_none() {
-                    return Err(ContractError::CustomError {
-                        val: info_sender.to_string()
-                            + &" Address is not correct reward snip contract".to_string(),
+                    return Err(ContractError::WrongRewardContract {
+                        address: info_sender.to_string(),
                     });
                 }
                 let reward_contract = &mut state.reward_contracts[reward_contract_index.unwrap()];
@@ -163,9 +288,7 @@ fn receive(
             }
         }
     } else {
-        return Err(ContractError::CustomError {
-            val: "data should be given".to_string(),
-        });
+        return Err(ContractError::MissingMessage {});
     }
 
     Ok(Response::new().add_messages(response_msgs))
@@ -178,187 +301,710 @@ fn try_batch_receive(
     token_ids: Vec<String>,
     msg: Option<Binary>,
 ) -> Result<Response, ContractError> {
-    deps.api.debug(&format!("Receive received"));
     let mut response_msgs: Vec<CosmosMsg> = Vec::new();
     let mut state = CONFIG_ITEM.load(deps.storage)?;
-    if !state.is_active {
-        return Err(ContractError::CustomError {
-            val: "You cannot perform this action right now".to_string(),
-        });
-    }
-
-    if sender != &state.nft_contract.address {
-        return Err(ContractError::CustomError {
-            val: sender.to_string() + &" Address is not correct snip contract".to_string(),
-        });
-    }
+    check_burnable(&state, _env.block.time.seconds())?;
+
+    let collection = state
+        .nft_contracts
+        .iter()
+        .find(|x| &x.contract.address == sender)
+        .cloned()
+        .ok_or_else(|| ContractError::WrongNftContract {
+            address: sender.to_string(),
+        })?;
 
     if let Some(bin_msg) = msg {
         match from_binary(&bin_msg)? {
             HandleNftReceiveMsg::ClaimBurnRewards {
                 expected_rewards,
                 message,
+                rank_proofs,
+                deadline,
+                token_minimums,
+                require_all_rewards,
             } => {
+                if let Some(deadline) = deadline.as_ref() {
+                    check_deadline(deadline, &_env)?;
+                }
                 let history_store = HISTORY_STORE.add_suffix(from.to_string().as_bytes());
                 let current_time = _env.block.time.seconds();
-                let mut rewards_map = std::collections::HashMap::new();
-                for reward_contract in state.reward_contracts.iter() {
-                    let reward = {
-                        Reward {
-                            base_reward: Uint128::from(0u128),
-                            bonus_reward: Uint128::from(0u128),
+
+                let outcome = settle_claim(
+                    deps.as_ref(),
+                    &_env,
+                    &state,
+                    &collection,
+                    from,
+                    &token_ids,
+                    &message,
+                    rank_proofs,
+                    true,
+                )?;
+                let payouts = check_claim(
+                    &mut state,
+                    &outcome,
+                    &expected_rewards,
+                    &token_minimums.unwrap_or_default(),
+                    require_all_rewards.unwrap_or(false),
+                )?;
+                record_wallet_burns(
+                    deps.storage,
+                    &state,
+                    from,
+                    token_ids.len() as u32,
+                    current_time,
+                )?;
+                save_trait_burns(deps.storage, &collection, &outcome.trait_burns)?;
+                for history in outcome.histories.iter() {
+                    history_store.push(deps.storage, history)?;
+                }
+                for (token_id, meta) in token_ids.iter().zip(outcome.burned_meta.iter()) {
+                    let full_history: HistoryFull = {
+                        HistoryFull {
+                            date: current_time,
+                            token_id: token_id.to_string(),
+                            nft_contract: collection.contract.address.clone(),
+                            meta_data: meta.clone().unwrap_or_default(),
+                            message: message.to_string(),
+                            upgrade: None,
                         }
                     };
-                    rewards_map.insert(reward_contract.name.to_string(), reward);
+                    BURN_HISTORY_STORE.push(deps.storage, &full_history)?;
                 }
-                let mut index = 0;
-                for token_id in token_ids.iter() {
-                    let response =
-                        get_estimated_rewards_mut(&token_id, &current_time, &state, &deps).unwrap();
-                    for res in response.iter() {
-                        if let Some(value) = rewards_map.get_mut(&res.reward_contract_name) {
-                            if index == 0 {
-                                value.bonus_reward = res.bonus_expected;
-                            }
-
-                            value.base_reward +=
-                                res.base_reward_expected + res.rank_reward_expected;
-
-                            let meta: NftDossier = nft_dossier_query(
-                                deps.querier,
-                                token_id.to_string(),
-                                None,
-                                None,
-                                BLOCK_SIZE,
-                                state.nft_contract.code_hash.clone(),
-                                state.nft_contract.address.to_string(),
-                            )?;
-                            if state.trait_restriction.is_some() {
-                                let trait_to_check = state.trait_restriction.as_ref().unwrap();
-                                let restricted_trait = meta
-                                    .public_metadata
-                                    .as_ref()
-                                    .unwrap()
-                                    .extension
-                                    .as_ref()
-                                    .unwrap()
-                                    .attributes
-                                    .as_ref()
-                                    .unwrap()
-                                    .iter()
-                                    .find(|&x| x.trait_type == Some(trait_to_check.to_string()));
-                                if restricted_trait.is_none() {
-                                    return Err(ContractError::CustomError {
-                                        val: "This NFT does not meet the requirements".to_string(),
-                                    });
-                                }
-                            }
-                            let history_rewards = if index == 0 {
-                                value.bonus_reward + value.base_reward
-                            } else {
-                                value.base_reward
-                            };
-                            let claim_history: History = {
-                                History {
-                                    token_id: token_id.to_string(),
-                                    date: current_time,
-                                    rewards: history_rewards,
-                                    message: message.to_string(),
-                                }
-                            };
-                            history_store.push(deps.storage, &claim_history)?;
-                            let full_history: HistoryFull = {
-                                HistoryFull {
-                                    date: current_time,
-                                    token_id: token_id.to_string(),
-                                    meta_data: meta.public_metadata.unwrap(),
-                                    message: message.to_string(),
-                                }
-                            };
-                            BURN_HISTORY_STORE.push(deps.storage, &full_history);
-                            state.total_burned_amount += 1;
-                            index = index + 1;
-                        }
+
+                let mut vesting_entries = VESTING_STORE.get(deps.storage, from).unwrap_or_default();
+                for payout in payouts.iter() {
+                    let reward_contract = state
+                        .reward_contracts
+                        .iter()
+                        .find(|x| x.name == payout.reward_contract_name)
+                        .ok_or_else(|| ContractError::RewardNotFound {
+                            name: payout.reward_contract_name.clone(),
+                        })?;
+                    let reset_clock = match reward_contract.bonus_reset {
+                        None | Some(BonusReset::OnBonusPayout) => payout.bonus > Uint128::zero(),
+                        Some(BonusReset::OnAnyPayout) => true,
+                        Some(BonusReset::Manual) => false,
+                    };
+                    if reset_clock {
+                        BONUS_CLOCKS_STORE.insert(
+                            deps.storage,
+                            &reward_contract.name,
+                            &current_time,
+                        )?;
                     }
-                }
 
-                for expected_reward in expected_rewards.iter() {
-                    if let Some(value) = rewards_map.get_mut(&expected_reward.reward_contract_name)
-                    {
-                        if value.base_reward >= expected_reward.base_reward_expected
-                            && value.bonus_reward >= expected_reward.bonus_expected
-                        {
-                            let rewards_to_claim = value.base_reward + value.bonus_reward;
-                            let reward_contract_index =
-                                state.reward_contracts.iter().position(|x| {
-                                    x.name == expected_reward.reward_contract_name.to_string()
-                                });
-                            let reward_contract =
-                                &mut state.reward_contracts[reward_contract_index.unwrap()];
-
-                            if rewards_to_claim < reward_contract.total_rewards {
-                                //claim rewards
-                                if value.bonus_reward > Uint128::from(0u128) {
-                                    state.burn_counter_date = current_time;
-                                }
-
-                                reward_contract.total_rewards -= rewards_to_claim;
-
-                                let cosmos_msg = transfer_msg(
-                                    from.to_string(),
-                                    rewards_to_claim,
-                                    None,
-                                    None,
-                                    BLOCK_SIZE,
-                                    reward_contract.code_hash.to_string(),
-                                    reward_contract.address.to_string(),
-                                )?;
-
-                                response_msgs.push(cosmos_msg);
-                            } else {
-                                return Err(ContractError::CustomError {
-                                    val: "Not enough rewards left".to_string(),
-                                });
-                            }
-                        } else {
-                            return Err(ContractError::CustomError {
-                                val: "Actual reward less than Expected reward".to_string(),
-                            });
-                        }
+                    if let Some(schedule) = reward_contract.vesting.clone() {
+                        let entry = VestingEntry {
+                            reward_contract: ContractInfo {
+                                code_hash: reward_contract.code_hash.clone(),
+                                address: reward_contract.address.clone(),
+                                name: reward_contract.name.clone(),
+                            },
+                            denom: reward_contract.denom.clone(),
+                            amount: payout.total,
+                            claimed: Uint128::zero(),
+                            start: current_time,
+                            schedule,
+                        };
+                        add_vesting(&mut vesting_entries, entry, current_time);
                     } else {
-                        return Err(ContractError::CustomError {
-                            val: "Reward not in map".to_string(),
-                        });
+                        response_msgs.push(payout_msg(reward_contract, from, payout.total)?);
                     }
                 }
 
+                state.total_burned_amount += token_ids.len() as u32;
+                if let Some(prng_seed) = outcome.prng_seed.as_ref() {
+                    state.prng_seed = prng_seed.clone();
+                }
+                if let Some(mint_reward) = state.mint_reward.as_ref() {
+                    let minted_before = minted_amount(deps.storage, mint_reward);
+                    let mut mints: Vec<Mint> = Vec::new();
+                    for group in outcome.burned_meta.chunks(mint_reward.burn_ratio as usize) {
+                        let burned: Vec<Option<&Metadata>> =
+                            group.iter().map(|x| x.as_ref()).collect();
+                        mints.push(Mint {
+                            token_id: Some(minted_token_id(
+                                mint_reward,
+                                minted_before + mints.len() as u32 + 1,
+                            )),
+                            owner: Some(from.to_string()),
+                            public_metadata: Some(minted_metadata(mint_reward, &burned)),
+                            private_metadata: mint_reward.private_metadata.clone(),
+                            memo: None,
+                        });
+                    }
+                    if !mints.is_empty() {
+                        add_minted(deps.storage, mint_reward, mints.len() as u32)?;
+                        response_msgs.push(mint_reward_msg(mint_reward, mints)?);
+                    }
+                }
                 CONFIG_ITEM.save(deps.storage, &state)?;
+                if !vesting_entries.is_empty() {
+                    VESTING_STORE.insert(deps.storage, from, &vesting_entries)?;
+                }
+                response_msgs.push(burn_tokens_msg(deps.storage, &collection, &token_ids)?);
+            }
+            HandleNftReceiveMsg::UpgradeToken {
+                keep_token_id,
+                viewing_key,
+                message,
+            } => {
+                let upgrade = collection
+                    .upgrade
+                    .as_ref()
+                    .ok_or(ContractError::UpgradeNotAvailable {})?;
+                if token_ids.len() != upgrade.burn_count as usize {
+                    return Err(ContractError::UpgradeBurnCount {
+                        count: upgrade.burn_count,
+                    });
+                }
+                if token_ids.contains(&keep_token_id) {
+                    return Err(ContractError::UpgradedTokenBurned {
+                        token_id: keep_token_id,
+                    });
+                }
+                check_upgrade_rights(deps.querier, &_env.contract.address, &collection.contract)?;
+                let current_time = _env.block.time.seconds();
+                record_wallet_burns(
+                    deps.storage,
+                    &state,
+                    from,
+                    token_ids.len() as u32,
+                    current_time,
+                )?;
 
-                let mut burns: Vec<Burn> = Vec::new();
-                burns.push(Burn {
-                    token_ids: token_ids.clone(),
-                    memo: None,
-                });
-
-                let cosmos_batch_msg = batch_burn_nft_msg(
-                    burns,
+                let kept: NftDossier = nft_dossier_query(
+                    deps.querier,
+                    keep_token_id.clone(),
+                    Some(ViewerInfo {
+                        address: from.to_string(),
+                        viewing_key,
+                    }),
                     None,
                     BLOCK_SIZE,
-                    state.nft_contract.code_hash.clone(),
-                    state.nft_contract.address.to_string(),
+                    collection.contract.code_hash.clone(),
+                    collection.contract.address.to_string(),
                 )?;
-                response_msgs.push(cosmos_batch_msg);
+                if kept.owner.as_ref() != Some(from) {
+                    return Err(ContractError::NotTokenOwner {
+                        token_id: keep_token_id,
+                    });
+                }
+                let before = kept.public_metadata.unwrap_or_default();
+                let after = upgraded_metadata(&upgrade.rules, &before)?;
+                let token_upgrade = TokenUpgrade {
+                    token_id: keep_token_id.clone(),
+                    before,
+                    after: after.clone(),
+                };
+
+                let history_store = HISTORY_STORE.add_suffix(from.to_string().as_bytes());
+                let mut trait_burns = TraitBurns::new();
+                for token_id in token_ids.iter() {
+                    let meta: NftDossier = nft_dossier_query(
+                        deps.querier,
+                        token_id.to_string(),
+                        None,
+                        None,
+                        BLOCK_SIZE,
+                        collection.contract.code_hash.clone(),
+                        collection.contract.address.to_string(),
+                    )?;
+                    if !meets_trait_rules(&state, &collection, meta.public_metadata.as_ref()) {
+                        return Err(ContractError::TraitRequirementFailed {
+                            token_id: token_id.clone(),
+                        });
+                    }
+                    add_trait_burns(&mut trait_burns, meta.public_metadata.as_ref());
+                    history_store.push(
+                        deps.storage,
+                        &History {
+                            token_id: token_id.to_string(),
+                            nft_contract: collection.contract.address.clone(),
+                            message: message.to_string(),
+                            date: current_time,
+                            rewards: Uint128::zero(),
+                            lottery_rolls: vec![],
+                            minted_token_id: None,
+                            recipe: None,
+                        },
+                    )?;
+                    BURN_HISTORY_STORE.push(
+                        deps.storage,
+                        &HistoryFull {
+                            token_id: token_id.to_string(),
+                            nft_contract: collection.contract.address.clone(),
+                            message: message.to_string(),
+                            date: current_time,
+                            meta_data: meta.public_metadata.unwrap_or_default(),
+                            upgrade: Some(token_upgrade.clone()),
+                        },
+                    )?;
+                    state.total_burned_amount += 1;
+                }
+                save_trait_burns(deps.storage, &collection, &trait_burns)?;
+                CONFIG_ITEM.save(deps.storage, &state)?;
+
+                response_msgs.push(set_metadata_msg(
+                    keep_token_id,
+                    Some(after),
+                    None,
+                    None,
+                    BLOCK_SIZE,
+                    collection.contract.code_hash.clone(),
+                    collection.contract.address.to_string(),
+                )?);
+                response_msgs.push(burn_tokens_msg(deps.storage, &collection, &token_ids)?);
             }
         }
     } else {
-        return Err(ContractError::CustomError {
-            val: "data should be given".to_string(),
-        });
+        return Err(ContractError::MissingMessage {});
     }
 
     Ok(Response::new().add_messages(response_msgs))
 }
 
+/// Rewards of a claim before the expected rewards are checked
+struct ClaimOutcome {
+    /// lowest base and rank rewards the lottery could pay, and the bonus, per
+    /// reward contract
+    rewards_map: std::collections::HashMap<String, Reward>,
+    /// base and rank rewards after the lottery, recipe rewards included
+    won_map: std::collections::HashMap<String, Uint128>,
+    /// highest base and rank rewards the lottery could pay, recipe rewards
+    /// included
+    highest_map: std::collections::HashMap<String, Uint128>,
+    /// base and rank rewards paid for each token id and reward contract, after
+    /// the lottery and recipes
+    token_payouts: std::collections::HashMap<(String, String), Uint128>,
+    histories: Vec<History>,
+    burned_meta: Vec<Option<Metadata>>,
+    trait_burns: TraitBurns,
+    /// next seed of the lottery rng, if it was rolled
+    prng_seed: Option<Vec<u8>>,
+    recipe: Option<String>,
+}
+
+/// Trait values burned earlier in the batch, not stored yet
+type TraitBurns = std::collections::BTreeMap<(String, String), u32>;
+
+fn check_burnable(state: &State, current_time: u64) -> Result<(), ContractError> {
+    if !state.is_active {
+        return Err(ContractError::Inactive {});
+    }
+    match campaign_status(state, current_time) {
+        CampaignStatus::Scheduled => Err(ContractError::CampaignNotStarted {
+            start_time: state.start_time.unwrap_or_default(),
+        }),
+        CampaignStatus::Ended => Err(ContractError::CampaignEnded {
+            end_time: state.end_time.unwrap_or_default(),
+        }),
+        CampaignStatus::Running => Ok(()),
+    }
+}
+
+/// Works out the rewards of burning `token_ids` without writing anything, the
+/// claim and its simulation share it. Without `roll_lottery` the lotteries pay
+/// their lowest prize
+#[allow(clippy::too_many_arguments)]
+fn settle_claim(
+    deps: Deps,
+    env: &Env,
+    state: &State,
+    collection: &NftCollection,
+    from: &Addr,
+    token_ids: &[String],
+    message: &str,
+    rank_proofs: Option<Vec<RankProof>>,
+    roll_lottery: bool,
+) -> Result<ClaimOutcome, ContractError> {
+    let current_time = env.block.time.seconds();
+    wallet_burns_after(
+        deps.storage,
+        state,
+        from,
+        token_ids.len() as u32,
+        current_time,
+    )?;
+    let minted_before = match state.mint_reward.as_ref() {
+        Some(mint_reward) => {
+            minted_count(mint_reward, token_ids.len())?;
+            minted_amount(deps.storage, mint_reward)
+        }
+        None => 0,
+    };
+    let proven_ranks = proven_ranks(deps.storage, collection, rank_proofs)?;
+    let mut burned_meta: Vec<Option<Metadata>> = Vec::new();
+    let mut histories: Vec<History> = Vec::new();
+    let mut token_payouts: std::collections::HashMap<(String, String), Uint128> =
+        std::collections::HashMap::new();
+    let mut trait_burns = TraitBurns::new();
+    let has_lottery = roll_lottery && state.reward_contracts.iter().any(|x| x.lottery.is_some());
+    let entropy = if has_lottery {
+        lottery_entropy(env, from.as_str())?
+    } else {
+        vec![]
+    };
+    let mut rng = if has_lottery {
+        Some(Prng::new(&state.prng_seed, &entropy))
+    } else {
+        None
+    };
+    let mut draw: u32 = 0;
+    // base rewards after the lottery, paid out once the expected rewards are met
+    let mut won_map: std::collections::HashMap<String, Uint128> = std::collections::HashMap::new();
+    let mut highest_map: std::collections::HashMap<String, Uint128> =
+        std::collections::HashMap::new();
+    let mut rewards_map = std::collections::HashMap::new();
+    for reward_contract in state.reward_contracts.iter() {
+        let reward = {
+            Reward {
+                base_reward: Uint128::from(0u128),
+                bonus_reward: Uint128::from(0u128),
+            }
+        };
+        rewards_map.insert(reward_contract.name.to_string(), reward);
+    }
+    for (index, token_id) in token_ids.iter().enumerate() {
+        let meta: NftDossier = nft_dossier_query(
+            deps.querier,
+            token_id.to_string(),
+            None,
+            None,
+            BLOCK_SIZE,
+            collection.contract.code_hash.clone(),
+            collection.contract.address.to_string(),
+        )?;
+        if !meets_trait_rules(state, collection, meta.public_metadata.as_ref()) {
+            return Err(ContractError::TraitRequirementFailed {
+                token_id: token_id.clone(),
+            });
+        }
+
+        let response = get_estimated_rewards(
+            token_id,
+            collection,
+            meta.public_metadata.as_ref(),
+            state.total_burned_amount + index as u32,
+            &current_time,
+            state,
+            &proven_ranks,
+            &trait_burns,
+            deps,
+        )?;
+        add_trait_burns(&mut trait_burns, meta.public_metadata.as_ref());
+        let mut history_rewards = Uint128::from(0u128);
+        let mut lottery_rolls: Vec<LotteryRoll> = Vec::new();
+        for res in response.iter() {
+            if let Some(value) = rewards_map.get_mut(&res.reward_contract_name) {
+                if index == 0 {
+                    value.bonus_reward = res.bonus_expected;
+                    history_rewards += res.bonus_expected;
+                }
+
+                let mut won = res.base_reward_expected;
+                let mut lowest = res.base_reward_expected;
+                let mut highest = res.base_reward_expected;
+                let prizes = state
+                    .reward_contracts
+                    .iter()
+                    .find(|x| x.name == res.reward_contract_name)
+                    .and_then(|x| x.lottery.as_ref());
+                if let Some(prizes) = prizes {
+                    lowest = lowest_prize(prizes, res.base_reward_expected);
+                    highest = highest_prize(prizes, res.base_reward_expected);
+                    won = lowest;
+                }
+                if let (Some(prizes), Some(rng)) = (prizes, rng.as_mut()) {
+                    let roll = next_roll(rng);
+                    let prize = prize_index(prizes, roll);
+                    won = prize_reward(&prizes[prize].outcome, res.base_reward_expected);
+                    lottery_rolls.push(LotteryRoll {
+                        reward_contract_name: res.reward_contract_name.clone(),
+                        roll,
+                        prize_index: prize as u32,
+                        reward: won,
+                        seed: Binary::from(state.prng_seed.clone()),
+                        entropy: Binary::from(entropy.clone()),
+                        draw,
+                    });
+                    draw += 1;
+                }
+
+                value.base_reward += lowest + res.rank_reward_expected;
+                *won_map.entry(res.reward_contract_name.clone()).or_default() +=
+                    won + res.rank_reward_expected;
+                *highest_map
+                    .entry(res.reward_contract_name.clone())
+                    .or_default() += highest + res.rank_reward_expected;
+                *token_payouts
+                    .entry((token_id.clone(), res.reward_contract_name.clone()))
+                    .or_default() += won + res.rank_reward_expected;
+                history_rewards += won + res.rank_reward_expected;
+            }
+        }
+
+        let claim_history: History = {
+            History {
+                token_id: token_id.to_string(),
+                nft_contract: collection.contract.address.clone(),
+                date: current_time,
+                rewards: history_rewards,
+                message: message.to_string(),
+                lottery_rolls,
+                minted_token_id: state
+                    .mint_reward
+                    .as_ref()
+                    .map(|x| minted_token_id(x, minted_before + index as u32 / x.burn_ratio + 1)),
+                recipe: None,
+            }
+        };
+        histories.push(claim_history);
+        burned_meta.push(meta.public_metadata);
+    }
+
+    let burned_attributes: Vec<&[Trait]> = burned_meta
+        .iter()
+        .map(|x| token_attributes(x.as_ref()))
+        .collect();
+    let recipe = find_recipe(&state.recipes, &burned_attributes);
+    if let Some(recipe) = recipe {
+        if recipe.replaces_token_rewards {
+            for history in histories.iter_mut() {
+                history.rewards = Uint128::zero();
+            }
+            for value in rewards_map.values_mut() {
+                value.base_reward = Uint128::zero();
+                histories[0].rewards += value.bonus_reward;
+            }
+            won_map.clear();
+            highest_map.clear();
+            token_payouts.clear();
+        }
+        for reward in recipe.rewards.iter() {
+            if let Some(value) = rewards_map.get_mut(&reward.reward_contract_name) {
+                value.base_reward += reward.amount;
+                *won_map
+                    .entry(reward.reward_contract_name.clone())
+                    .or_default() += reward.amount;
+                *highest_map
+                    .entry(reward.reward_contract_name.clone())
+                    .or_default() += reward.amount;
+                histories[0].rewards += reward.amount;
+            }
+        }
+        for history in histories.iter_mut() {
+            history.recipe = Some(recipe.name.clone());
+        }
+    }
+
+    Ok(ClaimOutcome {
+        recipe: recipe.map(|x| x.name.clone()),
+        rewards_map,
+        won_map,
+        highest_map,
+        token_payouts,
+        histories,
+        burned_meta,
+        trait_burns,
+        prng_seed: rng.as_mut().map(|x| x.rand_bytes().to_vec()),
+    })
+}
+
+fn check_deadline(deadline: &ClaimDeadline, env: &Env) -> Result<(), ContractError> {
+    let expired = match deadline {
+        ClaimDeadline::AtHeight { height } => env.block.height > *height,
+        ClaimDeadline::AtTime { time } => env.block.time.seconds() > *time,
+    };
+    if expired {
+        return Err(ContractError::ClaimExpired {});
+    }
+    Ok(())
+}
+
+/// Checks the claim against the expected rewards and takes the payouts out of
+/// the reward pools, only the expected reward contracts are paid
+fn check_claim(
+    state: &mut State,
+    outcome: &ClaimOutcome,
+    expected_rewards: &[ExpectedCheck],
+    token_minimums: &[TokenMinimum],
+    require_all_rewards: bool,
+) -> Result<Vec<ClaimPayout>, ContractError> {
+    if require_all_rewards {
+        if let Some(unlisted) = state.reward_contracts.iter().find(|x| {
+            !expected_rewards
+                .iter()
+                .any(|y| y.reward_contract_name == x.name)
+        }) {
+            return Err(ContractError::UnlistedRewardContract {
+                name: unlisted.name.clone(),
+            });
+        }
+    }
+    for minimum in token_minimums.iter() {
+        let actual = outcome
+            .token_payouts
+            .get(&(
+                minimum.token_id.clone(),
+                minimum.reward_contract_name.clone(),
+            ))
+            .copied()
+            .unwrap_or_default();
+        if actual < minimum.min_reward {
+            return Err(ContractError::TokenRewardBelowMinimum {
+                token_id: minimum.token_id.clone(),
+                reward_contract_name: minimum.reward_contract_name.clone(),
+                actual,
+                minimum: minimum.min_reward,
+            });
+        }
+    }
+
+    let mut payouts: Vec<ClaimPayout> = Vec::new();
+    for expected_reward in expected_rewards.iter() {
+        let value = outcome
+            .rewards_map
+            .get(&expected_reward.reward_contract_name)
+            .ok_or_else(|| ContractError::RewardNotFound {
+                name: expected_reward.reward_contract_name.clone(),
+            })?;
+        // the lottery has rolled already, the slippage is checked on what it paid
+        let reward = outcome
+            .won_map
+            .get(&expected_reward.reward_contract_name)
+            .copied()
+            .unwrap_or_default();
+        if reward < expected_reward.base_reward_expected
+            || value.bonus_reward < expected_reward.bonus_expected
+        {
+            let (actual, expected) = if reward < expected_reward.base_reward_expected {
+                (reward, expected_reward.base_reward_expected)
+            } else {
+                (value.bonus_reward, expected_reward.bonus_expected)
+            };
+            return Err(ContractError::SlippageExceeded {
+                reward_contract_name: expected_reward.reward_contract_name.clone(),
+                actual,
+                expected,
+            });
+        }
+
+        let rewards_to_claim = reward + value.bonus_reward;
+        let reward_contract = state
+            .reward_contracts
+            .iter_mut()
+            .find(|x| x.name == expected_reward.reward_contract_name)
+            .ok_or_else(|| ContractError::RewardNotFound {
+                name: expected_reward.reward_contract_name.clone(),
+            })?;
+        if rewards_to_claim >= reward_contract.total_rewards {
+            return Err(ContractError::InsufficientRewards {
+                reward_contract_name: reward_contract.name.clone(),
+                remaining: reward_contract.total_rewards,
+            });
+        }
+        reward_contract.total_rewards -= rewards_to_claim;
+        payouts.push(ClaimPayout {
+            reward_contract_name: reward_contract.name.clone(),
+            reward,
+            bonus: value.bonus_reward,
+            total: rewards_to_claim,
+            vested: reward_contract.vesting.is_some(),
+        });
+    }
+    Ok(payouts)
+}
+
+fn add_trait_burns(trait_burns: &mut TraitBurns, meta: Option<&Metadata>) {
+    for attribute in token_attributes(meta).iter() {
+        if let Some(trait_type) = attribute.trait_type.as_ref() {
+            *trait_burns
+                .entry((trait_type.to_string(), attribute.value.to_string()))
+                .or_default() += 1;
+        }
+    }
+}
+
+/// Adds the trait values burned by a claim or an upgrade to the counts of the
+/// scarcity bonus
+fn save_trait_burns(
+    storage: &mut dyn Storage,
+    collection: &NftCollection,
+    trait_burns: &TraitBurns,
+) -> StdResult<()> {
+    let store = TRAIT_BURNS_STORE.add_suffix(collection.contract.address.as_bytes());
+    for (key, count) in trait_burns.iter() {
+        let burned = store.get(storage, key).unwrap_or_default();
+        store.insert(storage, key, &(burned + count))?;
+    }
+    Ok(())
+}
+
+/// Checks the burn caps for the wallet and records the burned tokens on it
+fn record_wallet_burns(
+    storage: &mut dyn Storage,
+    state: &State,
+    from: &Addr,
+    count: u32,
+    current_time: u64,
+) -> Result<(), ContractError> {
+    let wallet_burns = wallet_burns_after(storage, state, from, count, current_time)?;
+    WALLET_BURNS_STORE.insert(storage, from, &wallet_burns)?;
+    Ok(())
+}
+
+/// The wallet's burns after burning `count` more tokens, if the caps allow it
+fn wallet_burns_after(
+    storage: &dyn Storage,
+    state: &State,
+    from: &Addr,
+    count: u32,
+    current_time: u64,
+) -> Result<WalletBurns, ContractError> {
+    let mut wallet_burns = WALLET_BURNS_STORE.get(storage, from).unwrap_or_default();
+    check_burn_caps(
+        &state.caps,
+        &mut wallet_burns,
+        state.total_burned_amount,
+        count,
+        current_time,
+    )?;
+    Ok(wallet_burns)
+}
+
+/// Counts the tokens as burned for the collection and burns them on it
+fn burn_tokens_msg(
+    storage: &mut dyn Storage,
+    collection: &NftCollection,
+    token_ids: &[String],
+) -> StdResult<CosmosMsg> {
+    let collection_burns = COLLECTION_BURNS_STORE
+        .get(storage, &collection.contract.address)
+        .unwrap_or(0);
+    COLLECTION_BURNS_STORE.insert(
+        storage,
+        &collection.contract.address,
+        &(collection_burns + token_ids.len() as u32),
+    )?;
+
+    let mut burns: Vec<Burn> = Vec::new();
+    burns.push(Burn {
+        token_ids: token_ids.to_vec(),
+        memo: None,
+    });
+
+    batch_burn_nft_msg(
+        burns,
+        None,
+        BLOCK_SIZE,
+        collection.contract.code_hash.clone(),
+        collection.contract.address.to_string(),
+    )
+}
+
 fn try_revoke_permit(
     deps: DepsMut,
     sender: &Addr,
@@ -376,27 +1022,24 @@ fn try_revoke_permit(
 
 fn try_update_reward_contract(
     deps: DepsMut,
+    env: Env,
     sender: &Addr,
     contracts: Vec<RewardsContractInfo>,
 ) -> Result<Response, ContractError> {
     let mut state = CONFIG_ITEM.load(deps.storage)?;
     let mut response_msgs: Vec<CosmosMsg> = Vec::new();
 
-    if sender.clone() != state.owner {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
-        });
-    }
+    check_role(&state, sender, Role::Operator)?;
+    validate_reward_contracts(&contracts)?;
+    validate_recipes(&state.recipes, &contracts)?;
 
     for reward_contract in state.reward_contracts.iter() {
         if reward_contract.total_rewards != Uint128::from(0u128) {
-            return Err(ContractError::CustomError {
-                val: "Clear out rewards first before updating".to_string(),
-            });
+            return Err(ContractError::RewardsNotCleared {});
         }
     }
 
-    for reward_contract in contracts.iter() {
+    for reward_contract in contracts.iter().filter(|x| x.denom.is_none()) {
         response_msgs.push(set_viewing_key_msg(
             state.viewing_key.clone().unwrap().to_string(),
             None,
@@ -406,242 +1049,1244 @@ fn try_update_reward_contract(
         )?);
     }
 
+    for reward_contract in state.reward_contracts.iter() {
+        if !contracts.iter().any(|x| x.name == reward_contract.name) {
+            BONUS_CLOCKS_STORE.remove(deps.storage, &reward_contract.name)?;
+        }
+    }
+    // new reward contracts start accruing their bonus now
+    for reward_contract in contracts.iter() {
+        if !BONUS_CLOCKS_STORE.contains(deps.storage, &reward_contract.name) {
+            BONUS_CLOCKS_STORE.insert(
+                deps.storage,
+                &reward_contract.name,
+                &env.block.time.seconds(),
+            )?;
+        }
+    }
+
     state.reward_contracts = contracts;
     CONFIG_ITEM.save(deps.storage, &state)?;
 
     Ok(Response::new().add_messages(response_msgs))
 }
 
+/// Ranks of one generation of a collection, generation 0 of the v1 nft
+/// contract is the unsuffixed store of the v1 layout
+fn rank_generation_store(
+    storage: &dyn Storage,
+    collection: &Addr,
+    generation: u32,
+) -> StdResult<RankStore> {
+    if generation == 0 && v1_nft_contract(storage)?.as_ref() == Some(collection) {
+        return Ok(RankStore::V1(v1_rank_store()));
+    }
+    Ok(RankStore::Ranks(RANK_STORE.add_suffix(
+        &[collection.as_bytes(), &generation.to_be_bytes()].concat(),
+    )))
+}
+
+/// Ranks the collection's tokens are rewarded with
+fn rank_store(storage: &dyn Storage, collection: &Addr) -> StdResult<RankStore> {
+    let generation = RANK_GENERATIONS_STORE
+        .get(storage, collection)
+        .unwrap_or_default();
+    rank_generation_store(storage, collection, generation)
+}
+
 fn try_update_ranks(
     deps: DepsMut,
     sender: &Addr,
+    nft_contract: Option<Addr>,
     ranks: Vec<Rank>,
 ) -> Result<Response, ContractError> {
     let state = CONFIG_ITEM.load(deps.storage)?;
-    let mut response_msgs: Vec<CosmosMsg> = Vec::new();
 
-    if sender.clone() != state.owner {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
-        });
-    }
+    check_role(&state, sender, Role::Operator)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
 
+    let store = rank_store(deps.storage, &collection.contract.address)?;
     for rank in ranks.iter() {
-        RANK_STORE.insert(deps.storage, &rank.token_id, &rank)?;
-    } 
- 
-    Ok(Response::default())
-}
-
-fn try_remove_rewards(deps: DepsMut, sender: &Addr) -> Result<Response, ContractError> {
-    let mut state = CONFIG_ITEM.load(deps.storage)?;
-    let mut response_msgs: Vec<CosmosMsg> = Vec::new();
-
-    if sender.clone() != state.owner {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
-        });
+        store.insert(deps.storage, &rank.token_id, rank.rank)?;
     }
 
-    for reward_contract in state.reward_contracts.iter_mut() {
-        let cosmos_msg = transfer_msg(
-            sender.to_string(),
-            reward_contract.total_rewards.clone(),
-            None,
-            None,
-            BLOCK_SIZE,
-            reward_contract.code_hash.to_string(),
-            reward_contract.address.to_string(),
-        )?;
-        response_msgs.push(cosmos_msg);
-
-        reward_contract.total_rewards = Uint128::from(0u128);
-    }
-    CONFIG_ITEM.save(deps.storage, &state)?;
-    Ok(Response::new().add_messages(response_msgs))
+    Ok(Response::default())
 }
 
-pub fn try_set_viewing_key(
+fn try_remove_ranks(
     deps: DepsMut,
-    _env: Env,
     sender: &Addr,
-    key: String,
+    nft_contract: Option<Addr>,
+    token_ids: Vec<String>,
 ) -> Result<Response, ContractError> {
     let state = CONFIG_ITEM.load(deps.storage)?;
-    let prng_seed: Vec<u8> = sha_256(base64::encode(key).as_bytes()).to_vec();
-    let viewing_key = base64::encode(&prng_seed);
 
-    let vk: ViewerInfo = {
-        ViewerInfo {
-            address: sender.to_string(),
-            viewing_key: viewing_key,
-        }
-    };
+    check_role(&state, sender, Role::Operator)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
 
-    if sender.clone() == state.owner {
-        ADMIN_VIEWING_KEY_ITEM.save(deps.storage, &vk)?;
-    } else {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
-        });
+    let store = rank_store(deps.storage, &collection.contract.address)?;
+    for token_id in token_ids.iter() {
+        if !store.contains(deps.storage, token_id) {
+            return Err(ContractError::RankNotFound {
+                token_id: token_id.clone(),
+            });
+        }
+        store.remove(deps.storage, token_id)?;
     }
+
     Ok(Response::default())
 }
 
-pub fn try_set_active_state(
+fn try_clear_ranks(
     deps: DepsMut,
-    _env: Env,
     sender: &Addr,
-    is_active: bool,
+    nft_contract: Option<Addr>,
+    limit: u32,
 ) -> Result<Response, ContractError> {
-    let mut state = CONFIG_ITEM.load(deps.storage)?;
+    let state = CONFIG_ITEM.load(deps.storage)?;
 
-    if sender.clone() != state.owner {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
+    check_role(&state, sender, Role::Operator)?;
+    if limit == 0 {
+        return Err(ContractError::InvalidConfig {
+            reason: "limit must be positive".to_string(),
         });
     }
-    state.is_active = is_active;
-
-    CONFIG_ITEM.save(deps.storage, &state)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+
+    let store = rank_store(deps.storage, &collection.contract.address)?;
+    let token_ids = store.token_ids(deps.storage, limit)?;
+    for token_id in token_ids.iter() {
+        store.remove(deps.storage, token_id)?;
+    }
+
+    Ok(Response::default()
+        .add_attribute("cleared_ranks", token_ids.len().to_string())
+        .add_attribute("remaining_ranks", store.get_len(deps.storage)?.to_string()))
+}
+
+fn try_set_rank_tiers(
+    deps: DepsMut,
+    sender: &Addr,
+    tiers: Vec<RankTier>,
+) -> Result<Response, ContractError> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    validate_rank_tiers(&tiers, &state.reward_contracts)?;
+    RANK_TIERS_ITEM.save(deps.storage, &tiers)?;
 
     Ok(Response::default())
 }
 
-pub fn try_reset_burn_counter_date(
+fn try_start_rarity(
+    deps: DepsMut,
+    sender: &Addr,
+    nft_contract: Option<Addr>,
+    method: RarityMethod,
+) -> Result<Response, ContractError> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+
+    let scan = RARITY_ITEM
+        .may_load(deps.storage)?
+        .map_or(0, |x| x.scan + 1);
+    RARITY_ITEM.save(
+        deps.storage,
+        &RarityStatus {
+            scan,
+            nft_contract: collection.contract.address.clone(),
+            method,
+            phase: RarityPhase::Ingesting,
+            tokens: 0,
+            cursor: 0,
+            start_after: None,
+            pass: 0,
+            left: 0,
+            right: 0,
+            last_score: None,
+            last_rank: 0,
+        },
+    )?;
+
+    Ok(Response::new().add_attribute("rarity_scan", scan.to_string()))
+}
+
+fn try_crank_rarity(
+    deps: DepsMut,
+    env: Env,
+    sender: &Addr,
+    limit: u32,
+) -> Result<Response, ContractError> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    if limit == 0 {
+        return Err(ContractError::InvalidConfig {
+            reason: "limit must be positive".to_string(),
+        });
+    }
+
+    let mut status = RARITY_ITEM
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoRarityScan {})?;
+    match status.phase {
+        RarityPhase::Ingesting => ingest_rarity(
+            deps.storage,
+            deps.querier,
+            &env.contract.address,
+            &state,
+            &mut status,
+            limit,
+        )?,
+        RarityPhase::Scoring => score_rarity(deps.storage, &mut status, limit)?,
+        RarityPhase::Sorting => sort_rarity(deps.storage, &mut status, limit)?,
+        RarityPhase::Ranking => rank_rarity(deps.storage, &mut status, limit)?,
+        RarityPhase::Done => return Err(ContractError::RarityScanDone {}),
+    }
+    RARITY_ITEM.save(deps.storage, &status)?;
+
+    Ok(Response::new()
+        .add_attribute("rarity_tokens", status.tokens.to_string())
+        .add_attribute("rarity_cursor", status.cursor.to_string()))
+}
+
+/// Counts the traits of the next page of the collection's tokens, a page
+/// shorter than `limit` ends the ingestion
+fn ingest_rarity(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    contract_address: &Addr,
+    state: &State,
+    status: &mut RarityStatus,
+    limit: u32,
+) -> Result<(), ContractError> {
+    let collection = find_collection(state, Some(&status.nft_contract))?;
+    let suffix = status.scan.to_be_bytes();
+    let tokens_store = RARITY_TOKENS_STORE.add_suffix(&suffix);
+    let values_store = RARITY_VALUES_STORE.add_suffix(&suffix);
+    let types_store = RARITY_TYPES_STORE.add_suffix(&suffix);
+    let sizes_store = RARITY_SIZES_STORE.add_suffix(&suffix);
+
+    let token_list = all_tokens_query(
+        querier,
+        state.viewing_key.clone().map(|viewing_key| ViewerInfo {
+            address: contract_address.to_string(),
+            viewing_key,
+        }),
+        status.start_after.clone(),
+        Some(limit),
+        BLOCK_SIZE,
+        collection.contract.code_hash.clone(),
+        collection.contract.address.to_string(),
+    )?;
+    for token_id in token_list.tokens.iter() {
+        let meta: NftDossier = nft_dossier_query(
+            querier,
+            token_id.to_string(),
+            None,
+            None,
+            BLOCK_SIZE,
+            collection.contract.code_hash.clone(),
+            collection.contract.address.to_string(),
+        )?;
+        // a trait type counts once per token
+        let mut traits: Vec<(String, String)> = Vec::new();
+        for attribute in token_attributes(meta.public_metadata.as_ref()) {
+            if let Some(trait_type) = attribute.trait_type.as_ref() {
+                if !traits.iter().any(|x| &x.0 == trait_type) {
+                    traits.push((trait_type.to_string(), attribute.value.to_string()));
+                }
+            }
+        }
+        for key in traits.iter() {
+            let count = values_store.get(storage, key).unwrap_or_default();
+            values_store.insert(storage, key, &(count + 1))?;
+            let count = types_store.get(storage, &key.0).unwrap_or_default();
+            types_store.insert(storage, &key.0, &(count + 1))?;
+        }
+        let size = traits.len() as u32;
+        let count = sizes_store.get(storage, &size).unwrap_or_default();
+        sizes_store.insert(storage, &size, &(count + 1))?;
+        tokens_store.push(
+            storage,
+            &RarityToken {
+                token_id: token_id.to_string(),
+                traits,
+            },
+        )?;
+        status.tokens += 1;
+    }
+
+    if let Some(last) = token_list.tokens.last() {
+        status.start_after = Some(last.to_string());
+    }
+    if (token_list.tokens.len() as u32) < limit {
+        status.phase = RarityPhase::Scoring;
+        status.cursor = 0;
+    }
+    Ok(())
+}
+
+/// Scores of a scan after a sorting pass, pass 0 holds them in token order
+fn rarity_scores(scan: u32, pass: u32) -> AppendStore<'static, RarityScore> {
+    RARITY_SCORES_STORE.add_suffix(&[scan.to_be_bytes(), pass.to_be_bytes()].concat())
+}
+
+/// Scores the next `limit` ingested tokens, a scan without tokens stops here
+/// so it can't replace the collection's ranks with none
+fn score_rarity(
+    storage: &mut dyn Storage,
+    status: &mut RarityStatus,
+    limit: u32,
+) -> Result<(), ContractError> {
+    if status.tokens == 0 {
+        return Err(ContractError::EmptyRarityScan {});
+    }
+    let suffix = status.scan.to_be_bytes();
+    let tokens_store = RARITY_TOKENS_STORE.add_suffix(&suffix);
+    let values_store = RARITY_VALUES_STORE.add_suffix(&suffix);
+    let sizes_store = RARITY_SIZES_STORE.add_suffix(&suffix);
+    let scores_store = rarity_scores(status.scan, 0);
+    let type_counts: Vec<(String, u32)> = RARITY_TYPES_STORE
+        .add_suffix(&suffix)
+        .iter(storage)?
+        .collect::<StdResult<Vec<(String, u32)>>>()?;
+
+    let end = status.cursor.saturating_add(limit).min(status.tokens);
+    for index in status.cursor..end {
+        let token = tokens_store.get_at(storage, index)?;
+        let value_counts: Vec<u32> = token
+            .traits
+            .iter()
+            .map(|x| values_store.get(storage, x).unwrap_or_default())
+            .collect();
+        let size_count = sizes_store
+            .get(storage, &(token.traits.len() as u32))
+            .unwrap_or_default();
+        let score = rarity_score(
+            &status.method,
+            &ScoreInput {
+                traits: &token.traits,
+                value_counts: &value_counts,
+                type_counts: &type_counts,
+                size_count,
+                total: status.tokens,
+            },
+        );
+        scores_store.push(
+            storage,
+            &RarityScore {
+                token_id: token.token_id,
+                score,
+            },
+        )?;
+    }
+    status.cursor = end;
+
+    if end == status.tokens {
+        status.phase = if status.tokens > 1 {
+            RarityPhase::Sorting
+        } else {
+            RarityPhase::Ranking
+        };
+        status.cursor = 0;
+    }
+    Ok(())
+}
+
+/// Merges pairs of sorted runs into the next pass, up to `limit` scores per
+/// crank, the scores are sorted once a run covers all of them
+fn sort_rarity(
+    storage: &mut dyn Storage,
+    status: &mut RarityStatus,
+    limit: u32,
+) -> Result<(), ContractError> {
+    let source = rarity_scores(status.scan, status.pass);
+    let target = rarity_scores(status.scan, status.pass + 1);
+    let width = 1u32 << status.pass;
+
+    let mut merged = 0;
+    while merged < limit && status.cursor < status.tokens {
+        let left_end = status.cursor.saturating_add(width).min(status.tokens);
+        let right_end = left_end.saturating_add(width).min(status.tokens);
+        let left = status.cursor + status.left;
+        let right = left_end + status.right;
+        let take_left = if left == left_end {
+            false
+        } else if right == right_end {
+            true
+        } else {
+            ranks_before(
+                &source.get_at(storage, left)?,
+                &source.get_at(storage, right)?,
+            )
+        };
+        if take_left {
+            let score = source.get_at(storage, left)?;
+            target.push(storage, &score)?;
+            status.left += 1;
+        } else {
+            let score = source.get_at(storage, right)?;
+            target.push(storage, &score)?;
+            status.right += 1;
+        }
+        merged += 1;
+
+        if status.cursor + status.left == left_end && left_end + status.right == right_end {
+            status.cursor = right_end;
+            status.left = 0;
+            status.right = 0;
+        }
+    }
+
+    if status.cursor == status.tokens {
+        status.pass += 1;
+        status.cursor = 0;
+        if width.saturating_mul(2) >= status.tokens {
+            status.phase = RarityPhase::Ranking;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the ranks of the next `limit` sorted tokens into a new generation of
+/// the collection's ranks, which replaces the current one once all tokens are
+/// ranked. Tokens the scan didn't cover lose their rank, uploaded ones included
+fn rank_rarity(
+    storage: &mut dyn Storage,
+    status: &mut RarityStatus,
+    limit: u32,
+) -> Result<(), ContractError> {
+    if status.tokens == 0 {
+        return Err(ContractError::EmptyRarityScan {});
+    }
+    let sorted = rarity_scores(status.scan, status.pass);
+    // every scan ranks into a generation of its own, generation 0 is uploaded
+    let generation = status.scan + 1;
+    let ranked = rank_generation_store(storage, &status.nft_contract, generation)?;
+
+    let end = status.cursor.saturating_add(limit).min(status.tokens);
+    for index in status.cursor..end {
+        let score = sorted.get_at(storage, index)?;
+        let previous = status.last_score.map(|x| (x.u128(), status.last_rank));
+        let rank = competition_rank(previous, score.score, index);
+        ranked.insert(storage, &score.token_id, rank)?;
+        status.last_score = Some(Uint128::from(score.score));
+        status.last_rank = rank;
+    }
+    status.cursor = end;
+
+    if end == status.tokens {
+        RANK_GENERATIONS_STORE.insert(storage, &status.nft_contract, &generation)?;
+        status.phase = RarityPhase::Done;
+    }
+    Ok(())
+}
+
+fn try_set_scarcity_bonus(
+    deps: DepsMut,
+    sender: &Addr,
+    nft_contract: Addr,
+    bonus: Option<ScarcityBonus>,
+) -> Result<Response, ContractError> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    if !state
+        .nft_contracts
+        .iter()
+        .any(|x| x.contract.address == nft_contract)
+    {
+        return Err(ContractError::ContractNotFound {
+            address: nft_contract.to_string(),
+        });
+    }
+
+    match bonus {
+        Some(bonus) => {
+            validate_scarcity_bonus(&bonus, &state.reward_contracts)?;
+            SCARCITY_STORE.insert(deps.storage, &nft_contract, &bonus)?;
+        }
+        None => SCARCITY_STORE.remove(deps.storage, &nft_contract)?,
+    }
+
+    Ok(Response::default())
+}
+
+fn try_set_rank_root(
+    deps: DepsMut,
+    sender: &Addr,
+    nft_contract: Option<Addr>,
+    root: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+    let address = &collection.contract.address;
+
+    match root {
+        Some(root) => {
+            if root.len() != 32 {
+                return Err(ContractError::InvalidConfig {
+                    reason: "rank root has to be a 32 byte hash".to_string(),
+                });
+            }
+            RANK_ROOTS_STORE.insert(deps.storage, address, &root)?;
+        }
+        None => RANK_ROOTS_STORE.remove(deps.storage, address)?,
+    }
+
+    Ok(Response::default())
+}
+
+fn try_import_ranks(
+    deps: DepsMut,
+    sender: &Addr,
+    nft_contract: Option<Addr>,
+    data: Binary,
+) -> Result<Response, ContractError> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+
+    let ranks = decode_ranks(data.as_slice())?;
+    let store = rank_store(deps.storage, &collection.contract.address)?;
+    for rank in ranks.iter() {
+        store.insert(deps.storage, &rank.token_id, rank.rank)?;
+    }
+
+    Ok(Response::default().add_attribute("imported_ranks", ranks.len().to_string()))
+}
+
+fn try_remove_rewards(deps: DepsMut, sender: &Addr) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+    let mut response_msgs: Vec<CosmosMsg> = Vec::new();
+
+    check_role(&state, sender, Role::Treasurer)?;
+
+    for reward_contract in state.reward_contracts.iter_mut() {
+        if reward_contract.denom.is_some() && reward_contract.total_rewards.is_zero() {
+            // bank sends of nothing are rejected
+            continue;
+        }
+        let cosmos_msg = payout_msg(reward_contract, sender, reward_contract.total_rewards)?;
+        response_msgs.push(cosmos_msg);
+
+        reward_contract.total_rewards = Uint128::from(0u128);
+    }
+    CONFIG_ITEM.save(deps.storage, &state)?;
+    Ok(Response::new().add_messages(response_msgs))
+}
+
+fn try_sweep_rewards(
     deps: DepsMut,
     _env: Env,
     sender: &Addr,
+    treasury: Addr,
 ) -> Result<Response, ContractError> {
     let mut state = CONFIG_ITEM.load(deps.storage)?;
+    let mut response_msgs: Vec<CosmosMsg> = Vec::new();
+
+    check_role(&state, sender, Role::Treasurer)?;
+    if campaign_status(&state, _env.block.time.seconds()) != CampaignStatus::Ended {
+        return Err(ContractError::CampaignNotEnded {});
+    }
+
+    for reward_contract in state.reward_contracts.iter_mut() {
+        if reward_contract.total_rewards.is_zero() {
+            continue;
+        }
+        let cosmos_msg = payout_msg(reward_contract, &treasury, reward_contract.total_rewards)?;
+        response_msgs.push(cosmos_msg);
 
+        reward_contract.total_rewards = Uint128::from(0u128);
+    }
+    CONFIG_ITEM.save(deps.storage, &state)?;
+    Ok(Response::new().add_messages(response_msgs))
+}
+
+pub fn try_set_viewing_key(
+    deps: DepsMut,
+    _env: Env,
+    sender: &Addr,
+    key: String,
+) -> Result<Response, ContractError> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
     if sender.clone() != state.owner {
-        return Err(ContractError::CustomError {
-            val: "You don't have the permissions to execute this command".to_string(),
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let prng_seed: Vec<u8> = sha_256(base64::encode(key).as_bytes()).to_vec();
+    let viewing_key = base64::encode(&prng_seed);
+
+    let vk: ViewerInfo = {
+        ViewerInfo {
+            address: sender.to_string(),
+            viewing_key: viewing_key,
+        }
+    };
+
+    ADMIN_VIEWING_KEY_ITEM.save(deps.storage, &vk)?;
+    Ok(Response::default())
+}
+
+pub fn try_set_active_state(
+    deps: DepsMut,
+    _env: Env,
+    sender: &Addr,
+    is_active: bool,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Pauser)?;
+    state.is_active = is_active;
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_set_trait_rules(
+    deps: DepsMut,
+    sender: &Addr,
+    trait_rules: Option<TraitRule>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    if let Some(rules) = trait_rules.as_ref() {
+        rules.validate()?;
+    }
+    state.trait_rules = trait_rules;
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_update_trait_weights(
+    deps: DepsMut,
+    sender: &Addr,
+    reward_contract_name: String,
+    weights: Vec<TraitWeight>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+
+    let reward_contract = state
+        .reward_contracts
+        .iter_mut()
+        .find(|x| x.name == reward_contract_name)
+        .ok_or_else(|| ContractError::RewardNotFound {
+            name: reward_contract_name.clone(),
+        })?;
+    if !reward_contract.burn_type.uses_traits() {
+        return Err(ContractError::NotTraitWeighted {
+            name: reward_contract_name,
         });
     }
-    state.burn_counter_date = _env.block.time.seconds();
+    reward_contract.burn_type = RewardStrategy::TraitWeighted { weights };
+    validate_reward_contracts(&state.reward_contracts)?;
 
     CONFIG_ITEM.save(deps.storage, &state)?;
 
     Ok(Response::default())
 }
 
-fn get_estimated_rewards(
-    token_id: &String,
-    current_time: &u64,
-    state: &State,
-    deps: Deps,
-) -> StdResult<Vec<ExpectedReward>> {
-    let mut expected_rewards: Vec<ExpectedReward> = Vec::new();
+pub fn try_add_nft_contract(
+    deps: DepsMut,
+    _env: Env,
+    sender: &Addr,
+    collection: NftCollection,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
 
-    for reward_contract in state.reward_contracts.iter() {
-        let mut bonus_reward = Uint128::from(0u128);
-        let mut rank_reward = Uint128::from(0u128);
-        let mut token_rank = None;
-
-        if reward_contract.burn_type == "rank" {
-            let rank_entity: Option<Rank> = RANK_STORE.get(deps.storage, &token_id);
-            //.ok_or_else(|| StdError::generic_err("Rank pool doesn't have token"))?;
-            if rank_entity.is_some() {
-                token_rank = Some(rank_entity.as_ref().unwrap().rank);
-                let r_reward = rank_entity
-                    .as_ref()
-                    .unwrap()
-                    .rank_rewards
-                    .iter()
-                    .find(|&x| x.reward_contract_name == reward_contract.name);
-                if r_reward.is_some() {
-                    rank_reward = r_reward.unwrap().rank_reward;
-                }
-            }
+    check_role(&state, sender, Role::Operator)?;
+    if state
+        .nft_contracts
+        .iter()
+        .any(|x| x.contract.address == collection.contract.address)
+    {
+        return Err(ContractError::ContractExists {
+            address: collection.contract.address.to_string(),
+        });
+    }
+    validate_collection(&collection, &state.reward_contracts)?;
+
+    let response_msgs = register_collection_msgs(
+        &_env.contract.code_hash,
+        state.viewing_key.as_ref().unwrap(),
+        &collection.contract,
+    )?;
+    state.nft_contracts.push(collection);
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(response_msgs))
+}
+
+pub fn try_remove_nft_contract(
+    deps: DepsMut,
+    sender: &Addr,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    let position = state
+        .nft_contracts
+        .iter()
+        .position(|x| x.contract.address == address);
+
+    if position.is_none() {
+        return Err(ContractError::ContractNotFound {
+            address: address.to_string(),
+        });
+    } else {
+        state.nft_contracts.remove(position.unwrap());
+    }
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+fn validate_collection(
+    collection: &NftCollection,
+    reward_contracts: &[RewardsContractInfo],
+) -> Result<(), ContractError> {
+    if let Some(trait_rules) = collection.trait_rules.as_ref() {
+        trait_rules.validate()?;
+    }
+    if let Some(upgrade) = collection.upgrade.as_ref() {
+        validate_upgrade(upgrade)?;
+    }
+    for multiplier in collection.reward_multipliers.iter() {
+        if !reward_contracts
+            .iter()
+            .any(|x| x.name == multiplier.reward_contract_name)
+        {
+            return Err(ContractError::RewardNotFound {
+                name: multiplier.reward_contract_name.clone(),
+            });
         }
+    }
+    Ok(())
+}
 
-        if reward_contract.bonus_hourly > Uint128::from(0u128) {
-            if current_time > &state.burn_counter_date {
-                let duration_seconds = current_time - state.burn_counter_date;
-                let hours = duration_seconds / 3600;
-                bonus_reward = Uint128::from(hours) * reward_contract.bonus_hourly;
-            }
+/// Lets the nft contract call BatchReceiveNft on burns and gives this contract
+/// a viewing key on it
+fn register_collection_msgs(
+    code_hash: &str,
+    viewing_key: &str,
+    nft_contract: &ContractInfo,
+) -> StdResult<Vec<CosmosMsg>> {
+    Ok(vec![
+        register_receive_nft_msg(
+            code_hash.to_string(),
+            Some(true),
+            None,
+            BLOCK_SIZE,
+            nft_contract.code_hash.clone(),
+            nft_contract.address.to_string(),
+        )?,
+        set_viewing_key_msg(
+            viewing_key.to_string(),
+            None,
+            BLOCK_SIZE,
+            nft_contract.code_hash.clone(),
+            nft_contract.address.to_string(),
+        )?,
+    ])
+}
+
+fn meets_trait_rules(state: &State, collection: &NftCollection, meta: Option<&Metadata>) -> bool {
+    let attributes = token_attributes(meta);
+    state
+        .trait_rules
+        .iter()
+        .chain(collection.trait_rules.iter())
+        .all(|x| x.matches(attributes))
+}
+
+/// The collection at `address`, the first one when it is `None`
+fn find_collection<'a>(
+    state: &'a State,
+    address: Option<&Addr>,
+) -> Result<&'a NftCollection, ContractError> {
+    match address {
+        Some(address) => state
+            .nft_contracts
+            .iter()
+            .find(|x| &x.contract.address == address)
+            .ok_or_else(|| ContractError::ContractNotFound {
+                address: address.to_string(),
+            }),
+        None => state
+            .nft_contracts
+            .first()
+            .ok_or(ContractError::NoNftContracts {}),
+    }
+}
+
+pub fn try_set_schedule(
+    deps: DepsMut,
+    sender: &Addr,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    validate_schedule(start_time, end_time)?;
+    state.start_time = start_time;
+    state.end_time = end_time;
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_set_burn_caps(
+    deps: DepsMut,
+    sender: &Addr,
+    caps: BurnCaps,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    state.caps = caps;
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+fn validate_schedule(start_time: Option<u64>, end_time: Option<u64>) -> Result<(), ContractError> {
+    if let (Some(start_time), Some(end_time)) = (start_time, end_time) {
+        if start_time >= end_time {
+            return Err(ContractError::InvalidSchedule {});
         }
+    }
+    Ok(())
+}
 
-        let expected_reward: ExpectedReward = {
-            ExpectedReward {
-                base_reward_expected: reward_contract.base_reward,
-                rank_reward_expected: rank_reward,
-                bonus_expected: bonus_reward,
-                total_expected: bonus_reward + rank_reward + reward_contract.base_reward,
-                rank: token_rank,
-                token_id: token_id.to_string(),
-                reward_contract_name: reward_contract.name.to_string(),
-            }
-        };
+/// Where the campaign is in its schedule, `is_active` is checked separately
+fn campaign_status(state: &State, current_time: u64) -> CampaignStatus {
+    if state.start_time.map_or(false, |x| current_time < x) {
+        CampaignStatus::Scheduled
+    } else if state.end_time.map_or(false, |x| current_time >= x) {
+        CampaignStatus::Ended
+    } else {
+        CampaignStatus::Running
+    }
+}
 
-        expected_rewards.push(expected_reward);
+/// Pays a reward in its native denom or through its SNIP-20 contract
+fn payout_msg(
+    reward_contract: &RewardsContractInfo,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    match reward_contract.denom.as_ref() {
+        Some(denom) => Ok(bank_send_msg(recipient, denom.clone(), amount)),
+        None => transfer_msg(
+            recipient.to_string(),
+            amount,
+            None,
+            None,
+            BLOCK_SIZE,
+            reward_contract.code_hash.to_string(),
+            reward_contract.address.to_string(),
+        ),
     }
-    return Ok(expected_rewards);
 }
-fn get_estimated_rewards_mut(
-    token_id: &String,
+
+fn bank_send_msg(recipient: &Addr, denom: String, amount: Uint128) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: vec![Coin { denom, amount }],
+    })
+}
+
+fn try_fund_native(deps: DepsMut, funds: &[Coin]) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+    if funds.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+    for coin in funds.iter() {
+        let reward_contract = state
+            .reward_contracts
+            .iter_mut()
+            .find(|x| x.denom.as_ref() == Some(&coin.denom))
+            .ok_or_else(|| ContractError::NotRewardDenom {
+                denom: coin.denom.clone(),
+            })?;
+        reward_contract.total_rewards += coin.amount;
+    }
+    CONFIG_ITEM.save(deps.storage, &state)?;
+    Ok(Response::default())
+}
+
+fn try_claim_vested(deps: DepsMut, env: Env, sender: &Addr) -> Result<Response, ContractError> {
+    let mut entries = VESTING_STORE.get(deps.storage, sender).unwrap_or_default();
+    let payouts = claim_vested(&mut entries, env.block.time.seconds());
+    if payouts.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    if entries.is_empty() {
+        VESTING_STORE.remove(deps.storage, sender)?;
+    } else {
+        VESTING_STORE.insert(deps.storage, sender, &entries)?;
+    }
+
+    let mut response_msgs: Vec<CosmosMsg> = Vec::new();
+    for payout in payouts.into_iter() {
+        response_msgs.push(match payout.denom {
+            Some(denom) => bank_send_msg(sender, denom, payout.amount),
+            None => transfer_msg(
+                sender.to_string(),
+                payout.amount,
+                None,
+                None,
+                BLOCK_SIZE,
+                payout.reward_contract.code_hash,
+                payout.reward_contract.address.to_string(),
+            )?,
+        });
+    }
+    Ok(Response::new().add_messages(response_msgs))
+}
+
+fn mint_reward_msg(mint_reward: &MintReward, mut mints: Vec<Mint>) -> StdResult<CosmosMsg> {
+    if mints.len() == 1 {
+        let mint = mints.remove(0);
+        return mint_nft_msg(
+            mint.token_id,
+            mint.owner,
+            mint.public_metadata,
+            mint.private_metadata,
+            mint.memo,
+            None,
+            BLOCK_SIZE,
+            mint_reward.contract.code_hash.clone(),
+            mint_reward.contract.address.to_string(),
+        );
+    }
+    batch_mint_nft_msg(
+        mints,
+        None,
+        BLOCK_SIZE,
+        mint_reward.contract.code_hash.clone(),
+        mint_reward.contract.address.to_string(),
+    )
+}
+
+pub fn try_set_mint_reward(
+    deps: DepsMut,
+    sender: &Addr,
+    mint_reward: Option<MintReward>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    if let Some(mint_reward) = mint_reward.as_ref() {
+        validate_mint_reward(mint_reward)?;
+    }
+    state.mint_reward = mint_reward;
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_add_recipe(
+    deps: DepsMut,
+    sender: &Addr,
+    recipe: Recipe,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    state.recipes.push(recipe);
+    validate_recipes(&state.recipes, &state.reward_contracts)?;
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_remove_recipe(
+    deps: DepsMut,
+    sender: &Addr,
+    name: String,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    let position = state.recipes.iter().position(|x| x.name == name);
+    match position {
+        Some(position) => state.recipes.remove(position),
+        None => {
+            return Err(ContractError::RecipeNotFound { name });
+        }
+    };
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_reset_burn_counter_date(
+    deps: DepsMut,
+    _env: Env,
+    sender: &Addr,
+    reward_contract_name: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    check_role(&state, sender, Role::Operator)?;
+    let now = _env.block.time.seconds();
+    if let Some(name) = reward_contract_name {
+        if !state.reward_contracts.iter().any(|x| x.name == name) {
+            return Err(ContractError::RewardNotFound { name });
+        }
+        BONUS_CLOCKS_STORE.insert(deps.storage, &name, &now)?;
+        return Ok(Response::default());
+    }
+
+    state.burn_counter_date = now;
+    for reward_contract in state.reward_contracts.iter() {
+        BONUS_CLOCKS_STORE.insert(deps.storage, &reward_contract.name, &now)?;
+    }
+
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+fn bonus_start(storage: &dyn Storage, state: &State, reward_contract_name: &str) -> u64 {
+    BONUS_CLOCKS_STORE
+        .get(storage, &reward_contract_name.to_string())
+        .unwrap_or(state.burn_counter_date)
+}
+
+/// Ranks of the proofs, all of them have to verify against the rank root
+fn proven_ranks(
+    storage: &dyn Storage,
+    collection: &NftCollection,
+    rank_proofs: Option<Vec<RankProof>>,
+) -> Result<Vec<Rank>, ContractError> {
+    let rank_proofs = rank_proofs.unwrap_or_default();
+    if rank_proofs.is_empty() {
+        return Ok(vec![]);
+    }
+    let root = RANK_ROOTS_STORE.get(storage, &collection.contract.address);
+    let mut ranks: Vec<Rank> = Vec::new();
+    for rank_proof in rank_proofs.into_iter() {
+        let verified = match root.as_ref() {
+            Some(root) => verify_rank(
+                root.as_slice(),
+                &collection.contract.address,
+                &rank_proof.rank,
+                &rank_proof.proof,
+            )?,
+            None => false,
+        };
+        if !verified {
+            return Err(ContractError::InvalidRankProof {
+                token_id: rank_proof.rank.token_id,
+            });
+        }
+        ranks.push(rank_proof.rank);
+    }
+    Ok(ranks)
+}
+
+fn get_estimated_rewards(
+    token_id: &str,
+    collection: &NftCollection,
+    meta: Option<&Metadata>,
+    burn_position: u32,
     current_time: &u64,
     state: &State,
-    deps: &DepsMut,
+    proven_ranks: &[Rank],
+    trait_burns: &TraitBurns,
+    deps: Deps,
 ) -> StdResult<Vec<ExpectedReward>> {
     let mut expected_rewards: Vec<ExpectedReward> = Vec::new();
 
-    for reward_contract in state.reward_contracts.iter() {
-        let mut bonus_reward = Uint128::from(0u128);
-        let mut rank_reward = Uint128::from(0u128);
-        let mut token_rank = None;
-
-        if reward_contract.burn_type == "rank" {
-            let rank_entity: Option<Rank> = RANK_STORE.get(deps.storage, &token_id);
-            //.ok_or_else(|| StdError::generic_err("Rank pool doesn't have token"))?;
-            if rank_entity.is_some() {
-                token_rank = Some(rank_entity.as_ref().unwrap().rank);
-                let r_reward = rank_entity
-                    .as_ref()
-                    .unwrap()
-                    .rank_rewards
-                    .iter()
-                    .find(|&x| x.reward_contract_name == reward_contract.name);
-                if r_reward.is_some() {
-                    rank_reward = r_reward.unwrap().rank_reward;
-                }
+    let uses_rank = state
+        .reward_contracts
+        .iter()
+        .any(|x| x.burn_type.uses_rank());
+    let rank_tiers: Vec<RankTier> = if uses_rank {
+        RANK_TIERS_ITEM.may_load(deps.storage)?.unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let rank: Option<u32> = if uses_rank {
+        let stored = rank_store(deps.storage, &collection.contract.address)?;
+        proven_ranks
+            .iter()
+            .find(|x| x.token_id == token_id)
+            .map(|x| x.rank)
+            .or_else(|| stored.get(deps.storage, &token_id.to_string()))
+    } else {
+        None
+    };
+    let ctx = TokenContext {
+        rank,
+        rank_tiers: &rank_tiers,
+        attributes: token_attributes(meta),
+        burn_position,
+    };
+    let scarcity = SCARCITY_STORE
+        .get(deps.storage, &collection.contract.address)
+        .map(|bonus| {
+            let stored = TRAIT_BURNS_STORE.add_suffix(collection.contract.address.as_bytes());
+            let multiplier = scarcity_multiplier(&bonus, ctx.attributes, |x| {
+                let key = (x.trait_type.clone(), x.value.clone());
+                stored.get(deps.storage, &key).unwrap_or_default()
+                    + trait_burns.get(&key).copied().unwrap_or_default()
+            });
+            (bonus, multiplier)
+        });
+
+    for reward_contract in state.reward_contracts.iter() {
+        let mut bonus_reward = Uint128::from(0u128);
+        let mut reward = strategy_reward(reward_contract, &ctx);
+        if let Some(multiplier) = collection
+            .reward_multipliers
+            .iter()
+            .find(|x| x.reward_contract_name == reward_contract.name)
+        {
+            reward.base_reward = reward.base_reward * multiplier.multiplier;
+        }
+        if let Some((bonus, multiplier)) = scarcity.as_ref() {
+            if bonus.reward_contract_names.contains(&reward_contract.name) {
+                reward.base_reward = reward.base_reward * *multiplier;
             }
         }
 
-        if reward_contract.bonus_hourly > Uint128::from(0u128) {
-            if current_time > &state.burn_counter_date {
-                let duration_seconds = current_time - state.burn_counter_date;
-                let hours = duration_seconds / 3600;
-                bonus_reward = Uint128::from(hours) * reward_contract.bonus_hourly;
-            }
+        let start = bonus_start(deps.storage, state, &reward_contract.name);
+        if current_time > &start {
+            let duration_seconds = current_time - start;
+            let hours = duration_seconds / 3600;
+            bonus_reward = accrued_bonus(reward_contract, hours);
         }
 
         let expected_reward: ExpectedReward = {
             ExpectedReward {
-                base_reward_expected: reward_contract.base_reward,
-                rank_reward_expected: rank_reward,
+                base_reward_expected: reward.base_reward,
+                rank_reward_expected: reward.rank_reward,
                 bonus_expected: bonus_reward,
-                total_expected: bonus_reward + rank_reward + reward_contract.base_reward,
-                rank: token_rank,
+                total_expected: bonus_reward + reward.rank_reward + reward.base_reward,
+                rank: reward.rank,
                 token_id: token_id.to_string(),
                 reward_contract_name: reward_contract.name.to_string(),
+                bonus_start: start,
             }
         };
+
         expected_rewards.push(expected_reward);
     }
     return Ok(expected_rewards);
 }
-//TODO: ADD QUERY FOR FULL HISTORY
+
+pub fn try_grant_role(
+    deps: DepsMut,
+    sender: &Addr,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    if sender.clone() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    grant_role(&mut state.roles, role, address);
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_revoke_role(
+    deps: DepsMut,
+    sender: &Addr,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    if sender.clone() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    revoke_role(&mut state.roles, role, &address)?;
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_propose_owner(
+    deps: DepsMut,
+    sender: &Addr,
+    new_owner: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    if sender.clone() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    state.pending_owner = new_owner;
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+pub fn try_accept_ownership(deps: DepsMut, sender: &Addr) -> Result<Response, ContractError> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+
+    match state.pending_owner.take() {
+        Some(pending_owner) if &pending_owner == sender => state.owner = pending_owner,
+        Some(_) => return Err(ContractError::Unauthorized {}),
+        None => return Err(ContractError::NoPendingOwner {}),
+    }
+    CONFIG_ITEM.save(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
 #[entry_point]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::GetBurnInfo {} => to_binary(&query_burn_info(deps)?),
-        QueryMsg::GetExpectedRewards { token_ids } => {
-            to_binary(&query_expected_rewards(deps, _env, token_ids)?)
+        QueryMsg::GetBurnInfo {} => to_binary(&query_burn_info(deps, _env)?),
+        QueryMsg::GetExpectedRewards {
+            token_ids,
+            nft_contract,
+            rank_proofs,
+        } => to_binary(&query_expected_rewards(
+            deps,
+            _env,
+            token_ids,
+            nft_contract,
+            rank_proofs,
+        )?),
+        QueryMsg::CheckTraitRules {
+            token_ids,
+            nft_contract,
+        } => to_binary(&query_check_trait_rules(deps, token_ids, nft_contract)?),
+        QueryMsg::MatchRecipe {
+            token_ids,
+            nft_contract,
+        } => to_binary(&query_match_recipe(deps, token_ids, nft_contract)?),
+        QueryMsg::GetBurnAllowance { permit } => {
+            to_binary(&query_burn_allowance(deps, _env, permit)?)
         }
+        QueryMsg::GetVesting { permit } => to_binary(&query_vesting(deps, _env, permit)?),
         QueryMsg::GetNumUserHistory { permit } => {
             to_binary(&query_num_user_history(deps, _env, permit)?)
         }
@@ -652,6 +2297,38 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         } => to_binary(&query_user_history(
             deps, _env, permit, start_page, page_size,
         )?),
+        QueryMsg::GetRoles {} => to_binary(&query_roles(deps)?),
+        QueryMsg::GetRank {
+            nft_contract,
+            token_id,
+        } => to_binary(&query_rank(deps, nft_contract, token_id)?),
+        QueryMsg::GetRanks {
+            nft_contract,
+            start_page,
+            page_size,
+        } => to_binary(&query_ranks(deps, nft_contract, start_page, page_size)?),
+        QueryMsg::GetRankTiers {} => to_binary(&RankTiersResponse {
+            tiers: RANK_TIERS_ITEM.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::GetRarityStatus {} => to_binary(&RarityStatusResponse {
+            status: RARITY_ITEM.may_load(deps.storage)?,
+        }),
+        QueryMsg::SimulateClaim {
+            token_ids,
+            owner,
+            nft_contract,
+            rank_proofs,
+            deadline,
+        } => to_binary(&query_simulate_claim(
+            deps,
+            _env,
+            token_ids,
+            owner,
+            nft_contract,
+            rank_proofs,
+            deadline,
+        )?),
+        QueryMsg::GetScarcity { nft_contract } => to_binary(&query_scarcity(deps, nft_contract)?),
         QueryMsg::GetNumFullHistory {} => to_binary(&query_num_full_history(deps, _env)?),
         QueryMsg::GetFullHistory {
             start_page,
@@ -663,15 +2340,98 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     }
 }
 
-fn query_burn_info(deps: Deps) -> StdResult<BurnInfoResponse> {
+fn query_scarcity(deps: Deps, nft_contract: Option<Addr>) -> StdResult<ScarcityResponse> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+    let bonus = SCARCITY_STORE.get(deps.storage, &collection.contract.address);
+    let trait_burns = TRAIT_BURNS_STORE.add_suffix(collection.contract.address.as_bytes());
+    let traits = bonus.as_ref().map_or(vec![], |bonus| {
+        bonus
+            .supplies
+            .iter()
+            .map(|x| {
+                let burned = trait_burns
+                    .get(deps.storage, &(x.trait_type.clone(), x.value.clone()))
+                    .unwrap_or_default();
+                TraitScarcity {
+                    trait_type: x.trait_type.clone(),
+                    value: x.value.clone(),
+                    total_supply: x.total_supply,
+                    burned,
+                    remaining: x.total_supply.saturating_sub(burned),
+                    multiplier: supply_multiplier(bonus.max_multiplier, x.total_supply, burned),
+                }
+            })
+            .collect()
+    });
+    Ok(ScarcityResponse { bonus, traits })
+}
+
+fn query_rank(deps: Deps, nft_contract: Option<Addr>, token_id: String) -> StdResult<RankResponse> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+    let store = rank_store(deps.storage, &collection.contract.address)?;
+    Ok(RankResponse {
+        rank: store
+            .get(deps.storage, &token_id)
+            .map(|rank| Rank { token_id, rank }),
+    })
+}
+
+fn query_ranks(
+    deps: Deps,
+    nft_contract: Option<Addr>,
+    start_page: u32,
+    page_size: u32,
+) -> StdResult<RanksResponse> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+    let store = rank_store(deps.storage, &collection.contract.address)?;
+    Ok(RanksResponse {
+        ranks: store.paging(deps.storage, start_page, page_size)?,
+        total: store.get_len(deps.storage)?,
+    })
+}
+
+fn query_roles(deps: Deps) -> StdResult<RolesResponse> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    Ok(RolesResponse {
+        owner: state.owner,
+        pending_owner: state.pending_owner,
+        roles: state.roles,
+    })
+}
+
+fn query_burn_info(deps: Deps, env: Env) -> StdResult<BurnInfoResponse> {
     let state = CONFIG_ITEM.load(deps.storage)?;
+    let status = campaign_status(&state, env.block.time.seconds());
+    let nft_contracts = state
+        .nft_contracts
+        .into_iter()
+        .map(|collection| CollectionBurnInfo {
+            total_burned_amount: COLLECTION_BURNS_STORE
+                .get(deps.storage, &collection.contract.address)
+                .unwrap_or(0),
+            collection,
+        })
+        .collect();
     Ok(BurnInfoResponse {
         total_burned_amount: state.total_burned_amount,
-        nft_contract: state.nft_contract,
+        nft_contracts,
         reward_contracts: state.reward_contracts,
-        trait_restriction: state.trait_restriction,
+        trait_rules: state.trait_rules,
         is_active: state.is_active,
         burn_counter_date: state.burn_counter_date,
+        start_time: state.start_time,
+        end_time: state.end_time,
+        status,
+        mint_reward: state.mint_reward,
+        minted_amount: state
+            .mint_reward
+            .as_ref()
+            .map_or(0, |x| minted_amount(deps.storage, x)),
+        recipes: state.recipes,
+        lottery_commitment: seed_commitment(&state.prng_seed),
     })
 }
 
@@ -679,13 +2439,49 @@ fn query_expected_rewards(
     deps: Deps,
     env: Env,
     token_ids: Vec<String>,
+    nft_contract: Option<Addr>,
+    rank_proofs: Option<Vec<RankProof>>,
 ) -> StdResult<ExpectedRewardResponse> {
     let state = CONFIG_ITEM.load(deps.storage)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+    let proven_ranks = proven_ranks(deps.storage, collection, rank_proofs)?;
     let current_time = env.block.time.seconds();
+    let uses_traits = state
+        .reward_contracts
+        .iter()
+        .any(|x| x.burn_type.uses_traits())
+        || SCARCITY_STORE.contains(deps.storage, &collection.contract.address);
     let mut estimated_rewards: Vec<Vec<ExpectedReward>> = Vec::new();
-    for token_id in token_ids.iter() {
-        let response = get_estimated_rewards(&token_id, &current_time, &state, deps);
-        estimated_rewards.push(response.unwrap());
+    let mut trait_burns = TraitBurns::new();
+    for (i, token_id) in token_ids.iter().enumerate() {
+        let meta: Option<Metadata> = if uses_traits {
+            nft_dossier_query(
+                deps.querier,
+                token_id.to_string(),
+                None,
+                None,
+                BLOCK_SIZE,
+                collection.contract.code_hash.clone(),
+                collection.contract.address.to_string(),
+            )?
+            .public_metadata
+        } else {
+            None
+        };
+        let burn_position = state.total_burned_amount + i as u32;
+        let response = get_estimated_rewards(
+            token_id,
+            collection,
+            meta.as_ref(),
+            burn_position,
+            &current_time,
+            &state,
+            &proven_ranks,
+            &trait_burns,
+            deps,
+        )?;
+        add_trait_burns(&mut trait_burns, meta.as_ref());
+        estimated_rewards.push(response);
     }
 
     Ok(ExpectedRewardResponse {
@@ -693,11 +2489,179 @@ fn query_expected_rewards(
     })
 }
 
+fn query_simulate_claim(
+    deps: Deps,
+    env: Env,
+    token_ids: Vec<String>,
+    owner: Addr,
+    nft_contract: Option<Addr>,
+    rank_proofs: Option<Vec<RankProof>>,
+    deadline: Option<ClaimDeadline>,
+) -> StdResult<SimulateClaimResponse> {
+    let mut state = CONFIG_ITEM.load(deps.storage)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?.clone();
+    // queries run without block randomness, so the lotteries are not rolled
+    let settled = deadline
+        .as_ref()
+        .map_or(Ok(()), |x| check_deadline(x, &env))
+        .and_then(|_| check_burnable(&state, env.block.time.seconds()))
+        .and_then(|_| {
+            settle_claim(
+                deps,
+                &env,
+                &state,
+                &collection,
+                &owner,
+                &token_ids,
+                "",
+                rank_proofs,
+                false,
+            )
+        });
+    let outcome = match settled {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            return Ok(SimulateClaimResponse {
+                expected_rewards: vec![],
+                payouts: vec![],
+                lottery_ranges: vec![],
+                recipe: None,
+                error: Some(error.to_string()),
+            })
+        }
+    };
+    let lottery_ranges: Vec<LotteryRange> = state
+        .reward_contracts
+        .iter()
+        .filter(|x| x.lottery.is_some())
+        .filter_map(|x| {
+            Some(LotteryRange {
+                reward_contract_name: x.name.clone(),
+                lowest: *outcome.won_map.get(&x.name)?,
+                highest: *outcome.highest_map.get(&x.name)?,
+            })
+        })
+        .collect();
+
+    // the lowest lottery outcome is suggested, a real roll never pays less;
+    // reward contracts paying nothing are left out, an empty pool would fail them
+    let expected_rewards: Vec<ExpectedCheck> = state
+        .reward_contracts
+        .iter()
+        .filter_map(|x| {
+            let value = outcome.rewards_map.get(&x.name)?;
+            let won = outcome.won_map.get(&x.name).copied().unwrap_or_default();
+            if value.base_reward.is_zero() && value.bonus_reward.is_zero() && won.is_zero() {
+                return None;
+            }
+            Some(ExpectedCheck {
+                base_reward_expected: value.base_reward,
+                bonus_expected: value.bonus_reward,
+                reward_contract_name: x.name.clone(),
+            })
+        })
+        .collect();
+    let (payouts, error) = match check_claim(&mut state, &outcome, &expected_rewards, &[], false) {
+        Ok(payouts) => (payouts, None),
+        Err(error) => (vec![], Some(error.to_string())),
+    };
+
+    Ok(SimulateClaimResponse {
+        expected_rewards,
+        payouts,
+        lottery_ranges,
+        recipe: outcome.recipe,
+        error,
+    })
+}
+
+fn query_check_trait_rules(
+    deps: Deps,
+    token_ids: Vec<String>,
+    nft_contract: Option<Addr>,
+) -> StdResult<Vec<TraitCheckResponse>> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+    let mut response: Vec<TraitCheckResponse> = Vec::new();
+    for token_id in token_ids.iter() {
+        let meets_requirements = if state.trait_rules.is_none() && collection.trait_rules.is_none()
+        {
+            true
+        } else {
+            let meta: NftDossier = nft_dossier_query(
+                deps.querier,
+                token_id.to_string(),
+                None,
+                None,
+                BLOCK_SIZE,
+                collection.contract.code_hash.clone(),
+                collection.contract.address.to_string(),
+            )?;
+            meets_trait_rules(&state, collection, meta.public_metadata.as_ref())
+        };
+        response.push(TraitCheckResponse {
+            token_id: token_id.to_string(),
+            meets_requirements,
+        });
+    }
+    Ok(response)
+}
+
+fn query_match_recipe(
+    deps: Deps,
+    token_ids: Vec<String>,
+    nft_contract: Option<Addr>,
+) -> StdResult<RecipeMatchResponse> {
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    let collection = find_collection(&state, nft_contract.as_ref())?;
+    let mut burned_meta: Vec<Option<Metadata>> = Vec::new();
+    for token_id in token_ids.iter() {
+        let meta: NftDossier = nft_dossier_query(
+            deps.querier,
+            token_id.to_string(),
+            None,
+            None,
+            BLOCK_SIZE,
+            collection.contract.code_hash.clone(),
+            collection.contract.address.to_string(),
+        )?;
+        burned_meta.push(meta.public_metadata);
+    }
+    let burned_attributes: Vec<&[Trait]> = burned_meta
+        .iter()
+        .map(|x| token_attributes(x.as_ref()))
+        .collect();
+    Ok(RecipeMatchResponse {
+        recipe: find_recipe(&state.recipes, &burned_attributes).cloned(),
+    })
+}
+
+fn query_burn_allowance(deps: Deps, env: Env, permit: Permit) -> StdResult<BurnAllowanceResponse> {
+    let user_raw = get_querier(deps, permit, env.contract.address)?;
+    let user = deps.api.addr_humanize(&user_raw)?;
+    let state = CONFIG_ITEM.load(deps.storage)?;
+    let wallet_burns = WALLET_BURNS_STORE
+        .get(deps.storage, &user)
+        .unwrap_or_default();
+    Ok(burn_allowance(
+        &state.caps,
+        wallet_burns,
+        state.total_burned_amount,
+        env.block.time.seconds(),
+    ))
+}
+
+fn query_vesting(deps: Deps, env: Env, permit: Permit) -> StdResult<Vec<VestingBalance>> {
+    let user_raw = get_querier(deps, permit, env.contract.address)?;
+    let user = deps.api.addr_humanize(&user_raw)?;
+    let entries = VESTING_STORE.get(deps.storage, &user).unwrap_or_default();
+    Ok(vesting_balances(&entries, env.block.time.seconds()))
+}
+
 fn query_num_user_history(deps: Deps, env: Env, permit: Permit) -> StdResult<u32> {
     let user_raw = get_querier(deps, permit, env.contract.address)?;
-    let history_store = HISTORY_STORE.add_suffix(&user_raw);
-    let num = history_store.get_len(deps.storage)?;
-    Ok(num)
+    let user = deps.api.addr_humanize(&user_raw)?;
+    user_history_len(deps.storage, &user)
 }
 
 fn query_user_history(
@@ -708,14 +2672,12 @@ fn query_user_history(
     page_size: u32,
 ) -> StdResult<Vec<History>> {
     let user_raw = get_querier(deps, permit, env.contract.address)?;
-    let history_store = HISTORY_STORE.add_suffix(&user_raw);
-    let history = history_store.paging(deps.storage, start_page, page_size)?;
-    Ok(history)
+    let user = deps.api.addr_humanize(&user_raw)?;
+    user_history(deps.storage, &user, start_page, page_size)
 }
 
 fn query_num_full_history(deps: Deps, env: Env) -> StdResult<u32> {
-    let num = BURN_HISTORY_STORE.get_len(deps.storage)?;
-    Ok(num)
+    full_history_len(deps.storage)
 }
 
 fn query_full_history(
@@ -724,8 +2686,7 @@ fn query_full_history(
     start_page: u32,
     page_size: u32,
 ) -> StdResult<Vec<HistoryFull>> {
-    let history = BURN_HISTORY_STORE.paging(deps.storage, start_page, page_size)?;
-    Ok(history)
+    full_history(deps.storage, start_page, page_size)
 }
 
 fn query_reward_balance(deps: Deps, env: Env, viewer: ViewerInfo) -> StdResult<Vec<Balance>> {
@@ -733,6 +2694,15 @@ fn query_reward_balance(deps: Deps, env: Env, viewer: ViewerInfo) -> StdResult<V
     let state = CONFIG_ITEM.load(deps.storage)?;
     let mut balances: Vec<Balance> = Vec::new();
     for reward_contract in state.reward_contracts.iter() {
+        if let Some(denom) = reward_contract.denom.as_ref() {
+            let balance = deps
+                .querier
+                .query_balance(env.contract.address.to_string(), denom)?;
+            balances.push(Balance {
+                amount: balance.amount,
+            });
+            continue;
+        }
         let balance = balance_query(
             deps.querier,
             env.contract.address.to_string(),
@@ -783,55 +2753,673 @@ fn get_querier(deps: Deps, permit: Permit, contract_address: Addr) -> StdResult<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::msg::ContractInfo;
-    use cosmwasm_std::testing::mock_dependencies;
+    use crate::merkle::{build_tree, leaf_hash};
+    use crate::msg::{BurnCountTier, BurnCurve, RewardStrategy};
+    use crate::testing::{test_collection, test_reward_contract, test_state};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
 
     #[test]
     fn rewards_calc() {
-        let mut deps = mock_dependencies();
-
-        let mut expected = Uint128::from(650000000u128);
+        let deps = mock_dependencies();
+        let expected = Uint128::from(650000000u128);
 
         let current_time = 1686675096;
-        let state: State = {
-            State {
-                owner: Addr::unchecked(""),
-                is_active: true,
-                nft_contract: {
-                    ContractInfo {
-                        code_hash: "".to_string(),
-                        address: Addr::unchecked(""),
-                        name: "".to_string(),
-                    }
-                },
-                reward_contracts: vec![RewardsContractInfo {
-                    code_hash: "".to_string(),
-                    address: Addr::unchecked(""),
-                    base_reward: Uint128::from(50000000u128),
-                    bonus_hourly: Uint128::from(25000000u128),
-                    name: "shill".to_string(),
-                    burn_type: "normal".to_string(),
-                    total_rewards: Uint128::from(10000000000000u128),
-                }],
-                viewing_key: None,
-                total_burned_amount: 200u32,
-                trait_restriction: None,
-                burn_counter_date: 1686588696,
-            }
+        let state = State {
+            nft_contracts: vec![test_collection("")],
+            reward_contracts: vec![RewardsContractInfo {
+                total_rewards: Uint128::from(10000000000000u128),
+                ..test_reward_contract("shill", 50000000, 25000000)
+            }],
+            total_burned_amount: 200u32,
+            burn_counter_date: 1686588696,
+            ..test_state()
         };
 
-        let x = get_estimated_rewards(&"1".to_string(), &current_time, &state, deps.as_ref());
-        println!("{:?}", x);
-        println!("{:?}", "HIII");
+        let x = get_estimated_rewards(
+            "1",
+            &state.nft_contracts[0],
+            None,
+            0,
+            &current_time,
+            &state,
+            &[],
+            &TraitBurns::new(),
+            deps.as_ref(),
+        );
 
         for value in x.unwrap().iter() {
             assert_eq!(value.total_expected, expected);
         }
-        //TODO TEST RANK BASED REWARDS
+    }
+
+    #[test]
+    fn strategy_rewards_follow_burn_position() {
+        let deps = mock_dependencies();
+        let current_time = 1686588696;
+        let mut state = State {
+            nft_contracts: vec![test_collection("")],
+            reward_contracts: vec![
+                RewardsContractInfo {
+                    burn_type: RewardStrategy::BurnCountTiers {
+                        tiers: vec![
+                            BurnCountTier {
+                                min_burned: 0,
+                                reward: Uint128::from(300u128),
+                            },
+                            BurnCountTier {
+                                min_burned: 10,
+                                reward: Uint128::from(200u128),
+                            },
+                        ],
+                    },
+                    ..test_reward_contract("tiers", 100, 0)
+                },
+                RewardsContractInfo {
+                    burn_type: RewardStrategy::Curve {
+                        curve: BurnCurve::Linear {
+                            decrement: Uint128::from(5u128),
+                            floor: Uint128::from(60u128),
+                        },
+                    },
+                    ..test_reward_contract("curve", 100, 0)
+                },
+            ],
+            burn_counter_date: 1686588696,
+            ..test_state()
+        };
+        validate_reward_contracts(&state.reward_contracts).unwrap();
+
+        let totals = |position: u32, state: &State| -> Vec<Uint128> {
+            get_estimated_rewards(
+                "1",
+                &state.nft_contracts[0],
+                None,
+                position,
+                &current_time,
+                state,
+                &[],
+                &TraitBurns::new(),
+                deps.as_ref(),
+            )
+            .unwrap()
+            .iter()
+            .map(|x| x.total_expected)
+            .collect()
+        };
+        assert_eq!(
+            totals(0, &state),
+            vec![Uint128::from(300u128), Uint128::from(100u128)]
+        );
+        assert_eq!(
+            totals(7, &state),
+            vec![Uint128::from(300u128), Uint128::from(65u128)]
+        );
+        assert_eq!(
+            totals(10, &state),
+            vec![Uint128::from(200u128), Uint128::from(60u128)]
+        );
+
+        state.reward_contracts[0].burn_type = RewardStrategy::BurnCountTiers {
+            tiers: vec![BurnCountTier {
+                min_burned: 5,
+                reward: Uint128::from(300u128),
+            }],
+        };
+        assert!(validate_reward_contracts(&state.reward_contracts).is_err());
+    }
+
+    #[test]
+    fn campaign_status_follows_schedule() {
+        let mut state = test_state();
+        assert_eq!(campaign_status(&state, 100), CampaignStatus::Running);
+
+        state.start_time = Some(100);
+        state.end_time = Some(200);
+        assert_eq!(campaign_status(&state, 99), CampaignStatus::Scheduled);
+        assert_eq!(campaign_status(&state, 100), CampaignStatus::Running);
+        assert_eq!(campaign_status(&state, 199), CampaignStatus::Running);
+        assert_eq!(campaign_status(&state, 200), CampaignStatus::Ended);
+        assert!(validate_schedule(Some(200), Some(100)).is_err());
+    }
+
+    #[test]
+    fn bonus_clocks_are_per_reward_contract() {
+        let mut deps = mock_dependencies();
+        let hour = 3600;
+        let mut state = test_state();
+        state.nft_contracts = vec![test_collection("nft")];
+        for name in ["first", "second"] {
+            state
+                .reward_contracts
+                .push(test_reward_contract(name, 0, 10));
+        }
+        BONUS_CLOCKS_STORE
+            .insert(deps.as_mut().storage, &"second".to_string(), &(4 * hour))
+            .unwrap();
+
+        let rewards = get_estimated_rewards(
+            "1",
+            &state.nft_contracts[0],
+            None,
+            0,
+            &(10 * hour),
+            &state,
+            &[],
+            &TraitBurns::new(),
+            deps.as_ref(),
+        )
+        .unwrap();
+        assert_eq!(rewards[0].bonus_start, 0);
+        assert_eq!(rewards[0].bonus_expected, Uint128::from(100u128));
+        assert_eq!(rewards[1].bonus_start, 4 * hour);
+        assert_eq!(rewards[1].bonus_expected, Uint128::from(60u128));
+    }
+
+    #[test]
+    fn native_rewards_pay_through_bank() {
+        let mut reward_contract = RewardsContractInfo {
+            denom: Some("uscrt".to_string()),
+            ..test_reward_contract("scrt", 100, 0)
+        };
+        let msg = payout_msg(
+            &reward_contract,
+            &Addr::unchecked("user"),
+            Uint128::from(5u128),
+        );
+        assert_eq!(
+            msg.unwrap(),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user".to_string(),
+                amount: vec![Coin {
+                    denom: "uscrt".to_string(),
+                    amount: Uint128::from(5u128),
+                }],
+            })
+        );
+
+        reward_contract.denom = None;
+        let msg = payout_msg(
+            &reward_contract,
+            &Addr::unchecked("user"),
+            Uint128::from(5u128),
+        );
+        assert!(matches!(msg.unwrap(), CosmosMsg::Wasm(_)));
+    }
+
+    #[test]
+    fn claim_bounds_are_enforced() {
+        let env = mock_env();
+        let height = env.block.height;
+        assert!(check_deadline(&ClaimDeadline::AtHeight { height }, &env).is_ok());
+        assert_eq!(
+            check_deadline(&ClaimDeadline::AtHeight { height: height - 1 }, &env),
+            Err(ContractError::ClaimExpired {})
+        );
+        let time = env.block.time.seconds();
+        assert!(check_deadline(&ClaimDeadline::AtTime { time }, &env).is_ok());
+        assert_eq!(
+            check_deadline(&ClaimDeadline::AtTime { time: time - 1 }, &env),
+            Err(ContractError::ClaimExpired {})
+        );
+
+        let mut state = test_state();
+        for name in ["shill", "scrt"] {
+            state.reward_contracts.push(RewardsContractInfo {
+                total_rewards: Uint128::from(1000u128),
+                ..test_reward_contract(name, 100, 0)
+            });
+        }
+        let mut outcome = ClaimOutcome {
+            rewards_map: std::collections::HashMap::new(),
+            won_map: std::collections::HashMap::new(),
+            highest_map: std::collections::HashMap::new(),
+            token_payouts: std::collections::HashMap::new(),
+            histories: vec![],
+            burned_meta: vec![],
+            trait_burns: TraitBurns::new(),
+            prng_seed: None,
+            recipe: None,
+        };
+        for name in ["shill", "scrt"] {
+            outcome.rewards_map.insert(
+                name.to_string(),
+                Reward {
+                    base_reward: Uint128::from(100u128),
+                    bonus_reward: Uint128::zero(),
+                },
+            );
+            outcome
+                .won_map
+                .insert(name.to_string(), Uint128::from(100u128));
+            outcome
+                .token_payouts
+                .insert(("1".to_string(), name.to_string()), Uint128::from(100u128));
+        }
+        let expected = vec![ExpectedCheck {
+            base_reward_expected: Uint128::from(100u128),
+            bonus_expected: Uint128::zero(),
+            reward_contract_name: "shill".to_string(),
+        }];
+
+        assert_eq!(
+            check_claim(&mut state.clone(), &outcome, &expected, &[], true),
+            Err(ContractError::UnlistedRewardContract {
+                name: "scrt".to_string(),
+            })
+        );
+        let minimum = |min_reward: u128| TokenMinimum {
+            token_id: "1".to_string(),
+            reward_contract_name: "shill".to_string(),
+            min_reward: Uint128::from(min_reward),
+        };
+        assert!(matches!(
+            check_claim(
+                &mut state.clone(),
+                &outcome,
+                &expected,
+                &[minimum(101)],
+                false
+            ),
+            Err(ContractError::TokenRewardBelowMinimum { .. })
+        ));
+        // a recipe replacing the token rewards pays nothing per token
+        let token_payouts = std::mem::take(&mut outcome.token_payouts);
+        assert_eq!(
+            check_claim(
+                &mut state.clone(),
+                &outcome,
+                &expected,
+                &[minimum(1)],
+                false
+            ),
+            Err(ContractError::TokenRewardBelowMinimum {
+                token_id: "1".to_string(),
+                reward_contract_name: "shill".to_string(),
+                actual: Uint128::zero(),
+                minimum: Uint128::from(1u128),
+            })
+        );
+        outcome.token_payouts = token_payouts;
+
+        // the lottery paid less than expected though its lowest prize would not
+        outcome
+            .won_map
+            .insert("shill".to_string(), Uint128::from(80u128));
+        assert_eq!(
+            check_claim(&mut state.clone(), &outcome, &expected, &[], false),
+            Err(ContractError::SlippageExceeded {
+                reward_contract_name: "shill".to_string(),
+                actual: Uint128::from(80u128),
+                expected: Uint128::from(100u128),
+            })
+        );
+        outcome
+            .won_map
+            .insert("shill".to_string(), Uint128::from(100u128));
+        let mut drained = state.clone();
+        drained.reward_contracts[0].total_rewards = Uint128::from(100u128);
+        assert_eq!(
+            check_claim(&mut drained, &outcome, &expected, &[], false),
+            Err(ContractError::InsufficientRewards {
+                reward_contract_name: "shill".to_string(),
+                remaining: Uint128::from(100u128),
+            })
+        );
+
+        let payouts = check_claim(&mut state, &outcome, &expected, &[minimum(100)], false).unwrap();
+        assert_eq!(payouts.len(), 1);
+        assert_eq!(payouts[0].total, Uint128::from(100u128));
+        assert_eq!(
+            state.reward_contracts[0].total_rewards,
+            Uint128::from(900u128)
+        );
+    }
+
+    #[test]
+    fn admin_messages_check_roles() {
+        let mut deps = mock_dependencies();
+        let state = State {
+            nft_contracts: vec![test_collection("nft")],
+            ..test_state()
+        };
+        CONFIG_ITEM.save(&mut deps.storage, &state).unwrap();
+        let owner = Addr::unchecked("owner");
+        let operator = Addr::unchecked("operator");
+        let next = Addr::unchecked("next");
+        let root = Some(Binary::from(vec![0u8; 32]));
+
+        assert_eq!(
+            try_set_rank_root(deps.as_mut(), &operator, None, root.clone()),
+            Err(ContractError::Unauthorized {})
+        );
+        assert_eq!(
+            try_grant_role(deps.as_mut(), &operator, Role::Operator, operator.clone()),
+            Err(ContractError::Unauthorized {})
+        );
+        try_grant_role(deps.as_mut(), &owner, Role::Operator, operator.clone()).unwrap();
+        try_set_rank_root(deps.as_mut(), &operator, None, root.clone()).unwrap();
+        assert_eq!(
+            try_set_viewing_key(deps.as_mut(), mock_env(), &operator, "key".to_string()),
+            Err(ContractError::Unauthorized {})
+        );
+        assert!(ADMIN_VIEWING_KEY_ITEM
+            .may_load(&deps.storage)
+            .unwrap()
+            .is_none());
+        try_set_viewing_key(deps.as_mut(), mock_env(), &owner, "key".to_string()).unwrap();
+        assert_eq!(
+            try_propose_owner(deps.as_mut(), &operator, Some(operator.clone())),
+            Err(ContractError::Unauthorized {})
+        );
+
+        assert_eq!(
+            try_accept_ownership(deps.as_mut(), &next),
+            Err(ContractError::NoPendingOwner {})
+        );
+        try_propose_owner(deps.as_mut(), &owner, Some(next.clone())).unwrap();
+        assert_eq!(
+            try_accept_ownership(deps.as_mut(), &operator),
+            Err(ContractError::Unauthorized {})
+        );
+        try_accept_ownership(deps.as_mut(), &next).unwrap();
+        assert_eq!(
+            try_revoke_role(deps.as_mut(), &owner, Role::Operator, operator.clone()),
+            Err(ContractError::Unauthorized {})
+        );
+        try_revoke_role(deps.as_mut(), &next, Role::Operator, operator.clone()).unwrap();
+        assert_eq!(
+            try_set_rank_root(deps.as_mut(), &operator, None, root),
+            Err(ContractError::Unauthorized {})
+        );
+    }
+
+    #[test]
+    fn unknown_collections_are_typed_errors() {
+        let mut deps = mock_dependencies();
+        let state = State {
+            nft_contracts: vec![test_collection("nft")],
+            ..test_state()
+        };
+        CONFIG_ITEM.save(&mut deps.storage, &state).unwrap();
+        let owner = Addr::unchecked("owner");
+
+        assert_eq!(
+            try_start_rarity(
+                deps.as_mut(),
+                &owner,
+                Some(Addr::unchecked("other")),
+                RarityMethod::TraitCount
+            ),
+            Err(ContractError::ContractNotFound {
+                address: "other".to_string(),
+            })
+        );
+        assert_eq!(
+            find_collection(&test_state(), None),
+            Err(ContractError::NoNftContracts {})
+        );
+        try_start_rarity(deps.as_mut(), &owner, None, RarityMethod::TraitCount).unwrap();
+    }
+
+    #[test]
+    fn clear_ranks_pages() {
+        let mut deps = mock_dependencies();
+        let state = State {
+            nft_contracts: vec![test_collection("nft")],
+            ..test_state()
+        };
+        CONFIG_ITEM.save(&mut deps.storage, &state).unwrap();
+        let owner = Addr::unchecked("owner");
+        let ranks: Vec<Rank> = (1..=5)
+            .map(|rank| Rank {
+                token_id: rank.to_string(),
+                rank,
+            })
+            .collect();
+        try_update_ranks(deps.as_mut(), &owner, None, ranks).unwrap();
+
+        let mut remaining: Vec<String> = Vec::new();
+        while remaining.last().map_or(true, |x| x != "0") {
+            let response = try_clear_ranks(deps.as_mut(), &owner, None, 2).unwrap();
+            remaining.push(response.attributes[1].value.clone());
+        }
+        assert_eq!(remaining, vec!["3", "1", "0"]);
+        assert!(try_clear_ranks(deps.as_mut(), &owner, None, 0).is_err());
+    }
+
+    #[test]
+    fn rarity_crank_pages_sort_and_ranks() {
+        let mut deps = mock_dependencies();
+        let scores = [
+            ("a", 3),
+            ("b", 9),
+            ("c", 5),
+            ("d", 9),
+            ("e", 1),
+            ("f", 5),
+            ("g", 7),
+        ];
+        for (token_id, score) in scores.iter() {
+            rarity_scores(0, 0)
+                .push(
+                    &mut deps.storage,
+                    &RarityScore {
+                        token_id: token_id.to_string(),
+                        score: *score,
+                    },
+                )
+                .unwrap();
+        }
+        let nft = Addr::unchecked("nft");
+        let other = Addr::unchecked("other");
+        // "z" was uploaded and is not in the scan, "c" is ranked in both
+        // collections
+        for (collection, token_id) in [(&nft, "c"), (&nft, "z"), (&other, "c")] {
+            rank_store(&deps.storage, collection)
+                .unwrap()
+                .insert(&mut deps.storage, &token_id.to_string(), 100)
+                .unwrap();
+        }
+        let mut status = RarityStatus {
+            scan: 0,
+            nft_contract: nft.clone(),
+            method: RarityMethod::TraitCount,
+            phase: RarityPhase::Sorting,
+            tokens: scores.len() as u32,
+            cursor: 0,
+            start_after: None,
+            pass: 0,
+            left: 0,
+            right: 0,
+            last_score: None,
+            last_rank: 0,
+        };
+
+        // every crank stops after two scores, in the middle of runs and pages
+        let mut cranks = 0;
+        while status.phase != RarityPhase::Done {
+            // the ranks of the collection stay in place until the scan is done
+            assert_eq!(
+                rank_store(&deps.storage, &nft)
+                    .unwrap()
+                    .get(&deps.storage, &"z".to_string()),
+                Some(100)
+            );
+            match status.phase {
+                RarityPhase::Sorting => sort_rarity(&mut deps.storage, &mut status, 2).unwrap(),
+                RarityPhase::Ranking => rank_rarity(&mut deps.storage, &mut status, 2).unwrap(),
+                phase => panic!("unexpected phase {:?}", phase),
+            }
+            cranks += 1;
+        }
+        // three passes of four cranks, then four ranking cranks
+        assert_eq!(cranks, 16);
+
+        let sorted: Vec<String> = rarity_scores(0, status.pass)
+            .paging(&deps.storage, 0, 10)
+            .unwrap()
+            .into_iter()
+            .map(|x| x.token_id)
+            .collect();
+        assert_eq!(sorted, vec!["b", "d", "g", "c", "f", "a", "e"]);
+        let rank = |collection: &Addr, token_id: &str| {
+            rank_store(&deps.storage, collection)
+                .unwrap()
+                .get(&deps.storage, &token_id.to_string())
+        };
+        let ranks: Vec<u32> = sorted.iter().map(|x| rank(&nft, x).unwrap()).collect();
+        assert_eq!(ranks, vec![1, 1, 3, 4, 4, 6, 7]);
+        // the finished scan replaces the uploaded ranks of the collection
+        assert_eq!(rank(&nft, "z"), None);
+        assert_eq!(rank(&other, "c"), Some(100));
+        assert_eq!(rank(&other, "a"), None);
+    }
+
+    #[test]
+    fn empty_rarity_scan_keeps_ranks() {
+        let mut deps = mock_dependencies();
+        let state = State {
+            nft_contracts: vec![test_collection("nft")],
+            ..test_state()
+        };
+        CONFIG_ITEM.save(&mut deps.storage, &state).unwrap();
+        let owner = Addr::unchecked("owner");
+        let nft = Addr::unchecked("nft");
+        rank_store(&deps.storage, &nft)
+            .unwrap()
+            .insert(&mut deps.storage, "z", 100)
+            .unwrap();
+
+        let mut status = RarityStatus {
+            scan: 0,
+            nft_contract: nft.clone(),
+            method: RarityMethod::TraitCount,
+            phase: RarityPhase::Scoring,
+            tokens: 0,
+            cursor: 0,
+            start_after: None,
+            pass: 0,
+            left: 0,
+            right: 0,
+            last_score: None,
+            last_rank: 0,
+        };
+        RARITY_ITEM.save(&mut deps.storage, &status).unwrap();
+        assert_eq!(
+            try_crank_rarity(deps.as_mut(), mock_env(), &owner, 10),
+            Err(ContractError::EmptyRarityScan {})
+        );
+        status.phase = RarityPhase::Ranking;
+        assert_eq!(
+            rank_rarity(&mut deps.storage, &mut status, 10),
+            Err(ContractError::EmptyRarityScan {})
+        );
+
+        assert_eq!(RANK_GENERATIONS_STORE.get(&deps.storage, &nft), None);
+        assert_eq!(
+            rank_store(&deps.storage, &nft)
+                .unwrap()
+                .get(&deps.storage, "z"),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn rank_roots_are_per_collection() {
+        let mut deps = mock_dependencies();
+        let state = State {
+            nft_contracts: vec![test_collection("nft"), test_collection("other")],
+            ..test_state()
+        };
+        CONFIG_ITEM.save(&mut deps.storage, &state).unwrap();
+        let owner = Addr::unchecked("owner");
+        let nft = Addr::unchecked("nft");
+        let rank = Rank {
+            token_id: "1".to_string(),
+            rank: 3,
+        };
+        let leaves = vec![
+            leaf_hash(&nft, &rank),
+            leaf_hash(
+                &nft,
+                &Rank {
+                    token_id: "2".to_string(),
+                    rank: 1,
+                },
+            ),
+        ];
+        let (root, proofs) = build_tree(&leaves);
+        try_set_rank_root(
+            deps.as_mut(),
+            &owner,
+            Some(nft.clone()),
+            Some(Binary::from(root.to_vec())),
+        )
+        .unwrap();
+        let proof = |proof: Vec<Binary>| {
+            Some(vec![RankProof {
+                rank: rank.clone(),
+                proof,
+            }])
+        };
+
+        assert_eq!(
+            proven_ranks(
+                &deps.storage,
+                &state.nft_contracts[0],
+                proof(proofs[0].clone())
+            ),
+            Ok(vec![rank.clone()])
+        );
+        // the other collection has no root of its own
+        assert_eq!(
+            proven_ranks(
+                &deps.storage,
+                &state.nft_contracts[1],
+                proof(proofs[0].clone())
+            ),
+            Err(ContractError::InvalidRankProof {
+                token_id: "1".to_string()
+            })
+        );
+        assert_eq!(
+            proven_ranks(
+                &deps.storage,
+                &state.nft_contracts[0],
+                proof(vec![Binary::from(vec![0u8; 33])])
+            ),
+            Err(ContractError::InvalidProofSibling {
+                token_id: "1".to_string()
+            })
+        );
+    }
 
-        // staked.staked_amount = Uint128::from(100u128);
-        // expected = Uint128::from(1369499999u128);
-        // let y = get_estimated_rewards(&staked, &current_time, &state);
-        // assert_eq!(y.unwrap(), expected);
+    #[test]
+    fn simulated_claims_check_the_deadline() {
+        let mut deps = mock_dependencies();
+        let state = State {
+            nft_contracts: vec![test_collection("nft")],
+            ..test_state()
+        };
+        CONFIG_ITEM.save(&mut deps.storage, &state).unwrap();
+        let env = mock_env();
+        let response = query_simulate_claim(
+            deps.as_ref(),
+            env.clone(),
+            vec!["1".to_string()],
+            Addr::unchecked("user"),
+            None,
+            None,
+            Some(ClaimDeadline::AtHeight {
+                height: env.block.height - 1,
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            response.error,
+            Some(ContractError::ClaimExpired {}.to_string())
+        );
+        assert!(response.payouts.is_empty());
+        assert!(response.lottery_ranges.is_empty());
     }
 }
@@ -1,18 +1,387 @@
-use cosmwasm_std::StdError;
+use crate::msg::Role;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
+/// Every error message starts with its stable code, e.g. `E3001: ...`, so
+/// clients can match on the code instead of the text
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     // let thiserror implement From<StdError> for you
     Std(#[from] StdError),
 
-    #[error("Unauthorized")]
+    #[error("E1001: unauthorized")]
     // issued when message sender != owner
     Unauthorized {},
 
-    #[error("Custom Error val: {val:?}")]
-    CustomError { val: String },
-    // Add any other custom errors you like here.
-    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+    #[error("E1002: burning is paused")]
+    Inactive {},
+
+    #[error("E1003: campaign starts at {start_time}")]
+    CampaignNotStarted { start_time: u64 },
+
+    #[error("E1004: campaign ended at {end_time}")]
+    CampaignEnded { end_time: u64 },
+
+    #[error("E1005: campaign has not ended")]
+    CampaignNotEnded {},
+
+    #[error("E1006: {address} does not hold the {role:?} role")]
+    RoleNotHeld { role: Role, address: String },
+
+    #[error("E1007: no ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("E2001: {address} is not a burnable nft contract")]
+    WrongNftContract { address: String },
+
+    #[error("E2002: {address} is not a reward contract")]
+    WrongRewardContract { address: String },
+
+    #[error("E2003: message is missing")]
+    MissingMessage {},
+
+    #[error("E2004: token {token_id} does not meet the trait requirements")]
+    TraitRequirementFailed { token_id: String },
+
+    #[error("E2005: tokens have to be burned in multiples of {multiple}")]
+    BurnMultiple { multiple: u32 },
+
+    #[error("E2006: tokens of this contract cannot be upgraded")]
+    UpgradeNotAvailable {},
+
+    #[error("E2007: an upgrade burns exactly {count} tokens")]
+    UpgradeBurnCount { count: u32 },
+
+    #[error("E2008: token {token_id} is upgraded and cannot be burned")]
+    UpgradedTokenBurned { token_id: String },
+
+    #[error("E2009: sender does not own token {token_id}")]
+    NotTokenOwner { token_id: String },
+
+    #[error("E2010: trait {trait_type} is not a number")]
+    TraitNotNumeric { trait_type: String },
+
+    #[error("E2011: trait {trait_type} is at its maximum")]
+    TraitAtMaximum { trait_type: String },
+
+    #[error("E2012: rank proof of token {token_id} is invalid")]
+    InvalidRankProof { token_id: String },
+
+    #[error("E2013: this contract is not a minter of {address}")]
+    UpgradeNotMinter { address: String },
+
+    #[error("E2014: minters of {address} may not update metadata")]
+    MetadataNotUpdatable { address: String },
+
+    #[error("E2015: rank proof of token {token_id} has a sibling that is not 32 bytes")]
+    InvalidProofSibling { token_id: String },
+
+    #[error("E3001: {reward_contract_name} reward {actual} is less than expected {expected}")]
+    SlippageExceeded {
+        reward_contract_name: String,
+        actual: Uint128,
+        expected: Uint128,
+    },
+
+    #[error("E3002: {reward_contract_name} has only {remaining} rewards left")]
+    InsufficientRewards {
+        reward_contract_name: String,
+        remaining: Uint128,
+    },
+
+    #[error("E3003: reward contract {name} not found")]
+    RewardNotFound { name: String },
+
+    #[error("E3004: rewards have to be removed first")]
+    RewardsNotCleared {},
+
+    #[error("E3005: nothing to claim")]
+    NothingToClaim {},
+
+    #[error("E3006: no coins were sent")]
+    NoFunds {},
+
+    #[error("E3007: {denom} is not a reward denom")]
+    NotRewardDenom { denom: String },
+
+    #[error("E3008: reward contract {name} is not trait weighted")]
+    NotTraitWeighted { name: String },
+
+    #[error("E3009: claim deadline passed")]
+    ClaimExpired {},
+
+    #[error("E3010: reward contract {name} is missing from the expected rewards")]
+    UnlistedRewardContract { name: String },
+
+    #[error("E3011: token {token_id} {reward_contract_name} reward {actual} is less than minimum {minimum}")]
+    TokenRewardBelowMinimum {
+        token_id: String,
+        reward_contract_name: String,
+        actual: Uint128,
+        minimum: Uint128,
+    },
+
+    #[error("E3012: the lottery cannot roll without block randomness")]
+    NoBlockRandom {},
+
+    #[error("E4001: at most {max} tokens can be burned per transaction")]
+    TxCapExceeded { max: u32 },
+
+    #[error("E4002: campaign burn cap reached, {remaining} tokens left")]
+    CampaignCapReached { remaining: u32 },
+
+    #[error("E4003: wallet burn cap reached, {remaining} tokens left")]
+    WalletCapReached { remaining: u32 },
+
+    #[error("E4004: wallet period cap reached, {remaining} tokens left until {resets_at}")]
+    PeriodCapReached { remaining: u32, resets_at: u64 },
+
+    #[error("E5001: at least one nft contract is required")]
+    NoNftContracts {},
+
+    #[error("E5002: contract {address} already exists")]
+    ContractExists { address: String },
+
+    #[error("E5003: contract {address} doesn't exist")]
+    ContractNotFound { address: String },
+
+    #[error("E5004: start time must be before end time")]
+    InvalidSchedule {},
+
+    #[error("E5005: invalid configuration, {reason}")]
+    InvalidConfig { reason: String },
+
+    #[error("E5006: recipe {name} doesn't exist")]
+    RecipeNotFound { name: String },
+
+    #[error("E5007: invalid rank data, {reason}")]
+    InvalidRankData { reason: String },
+
+    #[error("E5008: token {token_id} has no rank")]
+    RankNotFound { token_id: String },
+
+    #[error("E5009: no rarity scan was started")]
+    NoRarityScan {},
+
+    #[error("E5010: rarity scan is already done")]
+    RarityScanDone {},
+
+    #[error("E5011: rarity scan found no tokens")]
+    EmptyRarityScan {},
+}
+
+impl ContractError {
+    /// Stable code of the error, `None` for errors of the framework
+    pub fn code(&self) -> Option<u32> {
+        let code = match self {
+            ContractError::Std(_) => return None,
+            ContractError::Unauthorized {} => 1001,
+            ContractError::Inactive {} => 1002,
+            ContractError::CampaignNotStarted { .. } => 1003,
+            ContractError::CampaignEnded { .. } => 1004,
+            ContractError::CampaignNotEnded {} => 1005,
+            ContractError::RoleNotHeld { .. } => 1006,
+            ContractError::NoPendingOwner {} => 1007,
+            ContractError::WrongNftContract { .. } => 2001,
+            ContractError::WrongRewardContract { .. } => 2002,
+            ContractError::MissingMessage {} => 2003,
+            ContractError::TraitRequirementFailed { .. } => 2004,
+            ContractError::BurnMultiple { .. } => 2005,
+            ContractError::UpgradeNotAvailable {} => 2006,
+            ContractError::UpgradeBurnCount { .. } => 2007,
+            ContractError::UpgradedTokenBurned { .. } => 2008,
+            ContractError::NotTokenOwner { .. } => 2009,
+            ContractError::TraitNotNumeric { .. } => 2010,
+            ContractError::TraitAtMaximum { .. } => 2011,
+            ContractError::InvalidRankProof { .. } => 2012,
+            ContractError::UpgradeNotMinter { .. } => 2013,
+            ContractError::MetadataNotUpdatable { .. } => 2014,
+            ContractError::InvalidProofSibling { .. } => 2015,
+            ContractError::SlippageExceeded { .. } => 3001,
+            ContractError::InsufficientRewards { .. } => 3002,
+            ContractError::RewardNotFound { .. } => 3003,
+            ContractError::RewardsNotCleared {} => 3004,
+            ContractError::NothingToClaim {} => 3005,
+            ContractError::NoFunds {} => 3006,
+            ContractError::NotRewardDenom { .. } => 3007,
+            ContractError::NotTraitWeighted { .. } => 3008,
+            ContractError::ClaimExpired {} => 3009,
+            ContractError::UnlistedRewardContract { .. } => 3010,
+            ContractError::TokenRewardBelowMinimum { .. } => 3011,
+            ContractError::NoBlockRandom {} => 3012,
+            ContractError::TxCapExceeded { .. } => 4001,
+            ContractError::CampaignCapReached { .. } => 4002,
+            ContractError::WalletCapReached { .. } => 4003,
+            ContractError::PeriodCapReached { .. } => 4004,
+            ContractError::NoNftContracts {} => 5001,
+            ContractError::ContractExists { .. } => 5002,
+            ContractError::ContractNotFound { .. } => 5003,
+            ContractError::InvalidSchedule {} => 5004,
+            ContractError::InvalidConfig { .. } => 5005,
+            ContractError::RecipeNotFound { .. } => 5006,
+            ContractError::InvalidRankData { .. } => 5007,
+            ContractError::RankNotFound { .. } => 5008,
+            ContractError::NoRarityScan {} => 5009,
+            ContractError::RarityScanDone {} => 5010,
+            ContractError::EmptyRarityScan {} => 5011,
+        };
+        Some(code)
+    }
+}
+
+/// Lets queries return contract errors, their message keeps the code
+impl From<ContractError> for StdError {
+    fn from(error: ContractError) -> Self {
+        match error {
+            ContractError::Std(error) => error,
+            error => StdError::generic_err(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// one of every variant but `Std`, which has no code
+    fn every_error() -> Vec<ContractError> {
+        vec![
+            ContractError::Unauthorized {},
+            ContractError::Inactive {},
+            ContractError::CampaignNotStarted { start_time: 1 },
+            ContractError::CampaignEnded { end_time: 1 },
+            ContractError::CampaignNotEnded {},
+            ContractError::RoleNotHeld {
+                role: Role::Operator,
+                address: "x".to_string(),
+            },
+            ContractError::NoPendingOwner {},
+            ContractError::WrongNftContract {
+                address: "x".to_string(),
+            },
+            ContractError::WrongRewardContract {
+                address: "x".to_string(),
+            },
+            ContractError::MissingMessage {},
+            ContractError::TraitRequirementFailed {
+                token_id: "x".to_string(),
+            },
+            ContractError::BurnMultiple { multiple: 1 },
+            ContractError::UpgradeNotAvailable {},
+            ContractError::UpgradeBurnCount { count: 1 },
+            ContractError::UpgradedTokenBurned {
+                token_id: "x".to_string(),
+            },
+            ContractError::NotTokenOwner {
+                token_id: "x".to_string(),
+            },
+            ContractError::TraitNotNumeric {
+                trait_type: "x".to_string(),
+            },
+            ContractError::TraitAtMaximum {
+                trait_type: "x".to_string(),
+            },
+            ContractError::InvalidRankProof {
+                token_id: "x".to_string(),
+            },
+            ContractError::UpgradeNotMinter {
+                address: "x".to_string(),
+            },
+            ContractError::MetadataNotUpdatable {
+                address: "x".to_string(),
+            },
+            ContractError::InvalidProofSibling {
+                token_id: "x".to_string(),
+            },
+            ContractError::SlippageExceeded {
+                reward_contract_name: "x".to_string(),
+                actual: Uint128::from(1u128),
+                expected: Uint128::from(1u128),
+            },
+            ContractError::InsufficientRewards {
+                reward_contract_name: "x".to_string(),
+                remaining: Uint128::from(1u128),
+            },
+            ContractError::RewardNotFound {
+                name: "x".to_string(),
+            },
+            ContractError::RewardsNotCleared {},
+            ContractError::NothingToClaim {},
+            ContractError::NoFunds {},
+            ContractError::NotRewardDenom {
+                denom: "x".to_string(),
+            },
+            ContractError::NotTraitWeighted {
+                name: "x".to_string(),
+            },
+            ContractError::ClaimExpired {},
+            ContractError::UnlistedRewardContract {
+                name: "x".to_string(),
+            },
+            ContractError::TokenRewardBelowMinimum {
+                token_id: "x".to_string(),
+                reward_contract_name: "x".to_string(),
+                actual: Uint128::from(1u128),
+                minimum: Uint128::from(1u128),
+            },
+            ContractError::NoBlockRandom {},
+            ContractError::TxCapExceeded { max: 1 },
+            ContractError::CampaignCapReached { remaining: 1 },
+            ContractError::WalletCapReached { remaining: 1 },
+            ContractError::PeriodCapReached {
+                remaining: 1,
+                resets_at: 1,
+            },
+            ContractError::NoNftContracts {},
+            ContractError::ContractExists {
+                address: "x".to_string(),
+            },
+            ContractError::ContractNotFound {
+                address: "x".to_string(),
+            },
+            ContractError::InvalidSchedule {},
+            ContractError::InvalidConfig {
+                reason: "x".to_string(),
+            },
+            ContractError::RecipeNotFound {
+                name: "x".to_string(),
+            },
+            ContractError::InvalidRankData {
+                reason: "x".to_string(),
+            },
+            ContractError::RankNotFound {
+                token_id: "x".to_string(),
+            },
+            ContractError::NoRarityScan {},
+            ContractError::RarityScanDone {},
+            ContractError::EmptyRarityScan {},
+        ]
+    }
+
+    #[test]
+    fn messages_start_with_code() {
+        let errors = every_error();
+        let mut codes: Vec<u32> = Vec::new();
+        for error in errors.iter() {
+            let code = error.code().unwrap();
+            let prefix = format!("E{}: ", code);
+            assert!(error.to_string().starts_with(&prefix), "{}", error);
+            assert!(!codes.contains(&code), "{} is used twice", code);
+            codes.push(code);
+        }
+        assert_eq!(
+            ContractError::Std(StdError::generic_err("failed")).code(),
+            None
+        );
+        assert_eq!(
+            ContractError::SlippageExceeded {
+                reward_contract_name: "shill".to_string(),
+                actual: Uint128::from(5u128),
+                expected: Uint128::from(6u128),
+            }
+            .to_string(),
+            "E3001: shill reward 5 is less than expected 6"
+        );
+    }
 }
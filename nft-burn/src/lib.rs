@@ -1,6 +1,22 @@
+mod caps;
 pub mod contract;
 mod error;
+mod lottery;
+pub mod merkle;
+mod migrate;
+mod mint;
 pub mod msg;
 mod rand;
+mod ranks;
+mod rarity;
+mod recipe;
+mod roles;
+mod scarcity;
 pub mod state;
+mod strategy;
+#[cfg(test)]
+mod testing;
+mod traits;
+mod upgrade;
+mod vesting;
 pub use crate::error::ContractError;
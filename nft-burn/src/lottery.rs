@@ -0,0 +1,187 @@
+use crate::error::ContractError;
+use crate::msg::{LotteryOutcome, LotteryPrize, RewardsContractInfo};
+use crate::rand::{sha_256, Prng};
+use cosmwasm_std::{Binary, Env, Uint128};
+
+pub fn validate_lottery(reward_contract: &RewardsContractInfo) -> Result<(), ContractError> {
+    if let Some(prizes) = reward_contract.lottery.as_ref() {
+        if prizes.iter().map(|x| x.weight as u64).sum::<u64>() == 0 {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("{} lottery needs a prize with weight", reward_contract.name),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Entropy of the rolls of one transaction, the rng is `Prng::new(seed, entropy)`
+/// with the seed committed to in `GetBurnInfo` before the claim. The rest of
+/// the block is known before the claim is sent, so the rolls need the block's
+/// randomness
+pub fn lottery_entropy(env: &Env, from: &str) -> Result<Vec<u8>, ContractError> {
+    let random = env
+        .block
+        .random
+        .as_ref()
+        .ok_or(ContractError::NoBlockRandom {})?;
+    let mut entropy: Vec<u8> = random.to_vec();
+    entropy.extend_from_slice(&env.block.height.to_be_bytes());
+    entropy.extend_from_slice(&env.block.time.nanos().to_be_bytes());
+    entropy.extend_from_slice(from.as_bytes());
+    Ok(entropy)
+}
+
+/// Public commitment to the seed of the next claim's rolls
+pub fn seed_commitment(seed: &[u8]) -> Binary {
+    Binary::from(sha_256(seed).to_vec())
+}
+
+pub fn next_roll(rng: &mut Prng) -> u64 {
+    let bytes = rng.rand_bytes();
+    u64::from_be_bytes(bytes[..8].try_into().unwrap())
+}
+
+/// Index of the prize hit by `roll`, each prize covers `weight` consecutive
+/// values of `roll % total_weight`
+pub fn prize_index(prizes: &[LotteryPrize], roll: u64) -> usize {
+    let total: u64 = prizes.iter().map(|x| x.weight as u64).sum();
+    let mut target = roll % total;
+    for (i, prize) in prizes.iter().enumerate() {
+        if target < prize.weight as u64 {
+            return i;
+        }
+        target -= prize.weight as u64;
+    }
+    prizes.len() - 1
+}
+
+pub fn prize_reward(outcome: &LotteryOutcome, base_reward: Uint128) -> Uint128 {
+    match outcome {
+        LotteryOutcome::Multiplier { multiplier } => base_reward * *multiplier,
+        LotteryOutcome::Jackpot { amount } => *amount,
+    }
+}
+
+/// Lowest reward any prize with weight can pay for `base_reward`
+pub fn lowest_prize(prizes: &[LotteryPrize], base_reward: Uint128) -> Uint128 {
+    prizes
+        .iter()
+        .filter(|x| x.weight > 0)
+        .map(|x| prize_reward(&x.outcome, base_reward))
+        .min()
+        .unwrap_or(base_reward)
+}
+
+/// Highest reward any prize with weight can pay for `base_reward`
+pub fn highest_prize(prizes: &[LotteryPrize], base_reward: Uint128) -> Uint128 {
+    prizes
+        .iter()
+        .filter(|x| x.weight > 0)
+        .map(|x| prize_reward(&x.outcome, base_reward))
+        .max()
+        .unwrap_or(base_reward)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::Decimal;
+
+    #[test]
+    fn entropy_needs_block_random() {
+        let mut env = mock_env();
+        env.block.random = None;
+        assert_eq!(
+            lottery_entropy(&env, "user"),
+            Err(ContractError::NoBlockRandom {})
+        );
+
+        env.block.random = Some(Binary::from(vec![7u8; 32]));
+        let entropy = lottery_entropy(&env, "user").unwrap();
+        assert_eq!(&entropy[..32], &[7u8; 32]);
+        assert_ne!(lottery_entropy(&env, "other").unwrap(), entropy);
+    }
+
+    #[test]
+    fn rolls_follow_weights() {
+        let prizes = vec![
+            LotteryPrize {
+                weight: 90,
+                outcome: LotteryOutcome::Multiplier {
+                    multiplier: Decimal::one(),
+                },
+            },
+            LotteryPrize {
+                weight: 0,
+                outcome: LotteryOutcome::Jackpot {
+                    amount: Uint128::from(1u128),
+                },
+            },
+            LotteryPrize {
+                weight: 9,
+                outcome: LotteryOutcome::Multiplier {
+                    multiplier: Decimal::percent(500),
+                },
+            },
+            LotteryPrize {
+                weight: 1,
+                outcome: LotteryOutcome::Jackpot {
+                    amount: Uint128::from(100000u128),
+                },
+            },
+        ];
+        assert_eq!(prize_index(&prizes, 0), 0);
+        assert_eq!(prize_index(&prizes, 89), 0);
+        assert_eq!(prize_index(&prizes, 90), 2);
+        assert_eq!(prize_index(&prizes, 98), 2);
+        assert_eq!(prize_index(&prizes, 99), 3);
+        assert_eq!(prize_index(&prizes, 190), 2);
+
+        let base = Uint128::from(100u128);
+        assert_eq!(
+            prize_reward(&prizes[2].outcome, base),
+            Uint128::from(500u128)
+        );
+        assert_eq!(
+            prize_reward(&prizes[3].outcome, base),
+            Uint128::from(100000u128)
+        );
+        // the jackpot of 1 has no weight and is never hit
+        assert_eq!(lowest_prize(&prizes, base), base);
+        assert_eq!(highest_prize(&prizes, base), Uint128::from(100000u128));
+    }
+
+    #[test]
+    fn rolls_replay_from_seed_and_entropy() {
+        let prizes = vec![
+            LotteryPrize {
+                weight: 3,
+                outcome: LotteryOutcome::Multiplier {
+                    multiplier: Decimal::one(),
+                },
+            },
+            LotteryPrize {
+                weight: 1,
+                outcome: LotteryOutcome::Multiplier {
+                    multiplier: Decimal::percent(200),
+                },
+            },
+        ];
+        let seed = sha_256(b"seed");
+        let mut rng = Prng::new(&seed, b"entropy");
+        let rolls: Vec<u64> = (0..4000).map(|_| next_roll(&mut rng)).collect();
+
+        let mut replay = Prng::new(&seed, b"entropy");
+        for _ in 0..3 {
+            next_roll(&mut replay);
+        }
+        assert_eq!(next_roll(&mut replay), rolls[3]);
+
+        let hits = rolls
+            .iter()
+            .filter(|x| prize_index(&prizes, **x) == 1)
+            .count();
+        assert!((900..1100).contains(&hits), "{}", hits);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::error::ContractError;
+use crate::msg::Rank;
+use crate::rand::sha_256;
+use cosmwasm_std::{Addr, Binary};
+
+/// Hash of a rank leaf, the preimage is a `0x00` byte, the collection address
+/// and the token id and the rank as big endian `u32`. Strings are prefixed by
+/// their length as big endian `u32`
+pub fn leaf_hash(collection: &Addr, rank: &Rank) -> [u8; 32] {
+    let mut data: Vec<u8> = vec![0];
+    push_str(&mut data, collection.as_str());
+    push_str(&mut data, &rank.token_id);
+    data.extend_from_slice(&rank.rank.to_be_bytes());
+    sha_256(&data)
+}
+
+fn push_str(data: &mut Vec<u8>, value: &str) {
+    data.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    data.extend_from_slice(value.as_bytes());
+}
+
+/// Pairs are sorted before hashing so proofs don't need the sibling side
+fn node_hash(a: &[u8], b: &[u8]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut data: Vec<u8> = vec![1];
+    data.extend_from_slice(left);
+    data.extend_from_slice(right);
+    sha_256(&data)
+}
+
+/// Every sibling has to be a 32 byte hash, a shorter one could pass off part
+/// of a leaf preimage as a node
+pub fn verify_rank(
+    root: &[u8],
+    collection: &Addr,
+    rank: &Rank,
+    proof: &[Binary],
+) -> Result<bool, ContractError> {
+    let mut hash = leaf_hash(collection, rank);
+    for sibling in proof.iter() {
+        if sibling.len() != 32 {
+            return Err(ContractError::InvalidProofSibling {
+                token_id: rank.token_id.clone(),
+            });
+        }
+        hash = node_hash(&hash, sibling.as_slice());
+    }
+    Ok(hash.as_slice() == root)
+}
+
+/// Root of the tree over `leaves` and the proof of every leaf, a node without
+/// sibling moves up a level unchanged
+pub fn build_tree(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<Binary>>) {
+    let mut proofs: Vec<Vec<Binary>> = vec![Vec::new(); leaves.len()];
+    // leaf indexes below every node of the current level
+    let mut level: Vec<([u8; 32], Vec<usize>)> = leaves
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (*x, vec![i]))
+        .collect();
+    if level.is_empty() {
+        return ([0u8; 32], proofs);
+    }
+    while level.len() > 1 {
+        let mut next: Vec<([u8; 32], Vec<usize>)> = Vec::new();
+        for pair in level.chunks(2) {
+            match pair {
+                [(a, a_leaves), (b, b_leaves)] => {
+                    for i in a_leaves.iter() {
+                        proofs[*i].push(Binary::from(b.to_vec()));
+                    }
+                    for i in b_leaves.iter() {
+                        proofs[*i].push(Binary::from(a.to_vec()));
+                    }
+                    let mut below = a_leaves.clone();
+                    below.extend_from_slice(b_leaves);
+                    next.push((node_hash(a, b), below));
+                }
+                _ => next.push(pair[0].clone()),
+            }
+        }
+        level = next;
+    }
+    (level[0].0, proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rank(token_id: &str, rank: u32) -> Rank {
+        Rank {
+            token_id: token_id.to_string(),
+            rank,
+        }
+    }
+
+    #[test]
+    fn proofs_verify_against_root() {
+        let collection = Addr::unchecked("collection");
+        let ranks: Vec<Rank> = (1..=5).map(|x| rank(&x.to_string(), x)).collect();
+        let leaves: Vec<[u8; 32]> = ranks.iter().map(|x| leaf_hash(&collection, x)).collect();
+        let (root, proofs) = build_tree(&leaves);
+
+        for (rank, proof) in ranks.iter().zip(proofs.iter()) {
+            assert!(verify_rank(&root, &collection, rank, proof).unwrap());
+        }
+        let mut forged = ranks[0].clone();
+        forged.rank = 1000;
+        assert!(!verify_rank(&root, &collection, &forged, &proofs[0]).unwrap());
+        assert!(!verify_rank(&root, &collection, &ranks[1], &proofs[0]).unwrap());
+        // a proof of one collection doesn't hold for another
+        let other = Addr::unchecked("other");
+        assert!(!verify_rank(&root, &other, &ranks[0], &proofs[0]).unwrap());
+        // siblings have to be whole hashes
+        let mut short = proofs[0].clone();
+        short[0] = Binary::from(&short[0].as_slice()[..31]);
+        assert_eq!(
+            verify_rank(&root, &collection, &ranks[0], &short),
+            Err(ContractError::InvalidProofSibling {
+                token_id: "1".to_string()
+            })
+        );
+
+        // token ids longer than 255 bytes keep their full length in the leaf
+        let long = rank(&"a".repeat(300), 1);
+        let mut cut = long.clone();
+        cut.token_id.truncate(44);
+        assert_ne!(leaf_hash(&collection, &long), leaf_hash(&collection, &cut));
+    }
+}
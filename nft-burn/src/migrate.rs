@@ -0,0 +1,494 @@
+use crate::error::ContractError;
+use crate::msg::{
+    ContractInfo, History, HistoryFull, MigrateMsg, NftCollection, RewardStrategy,
+    RewardsContractInfo, TraitRule,
+};
+use crate::rand::sha_256;
+use crate::ranks::validate_rank_tiers;
+use crate::state::{
+    ContractVersion, State, BURN_HISTORY_KEY, BURN_HISTORY_STORE, COLLECTION_BURNS_STORE,
+    CONFIG_ITEM, CONFIG_KEY, CONTRACT_VERSION_ITEM, HISTORY_KEY, HISTORY_STORE,
+    HISTORY_V1_CONTRACT_KEY, RANK_KEY, RANK_TIERS_ITEM,
+};
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+use schemars::JsonSchema;
+use secret_toolkit::snip721::Metadata;
+use secret_toolkit::storage::{AppendStore, Item, Keymap};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+pub const CONTRACT_NAME: &str = "nft-burn";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// layout of `State` and the stores, bumped with every layout change
+pub const STATE_VERSION: u32 = 2;
+
+/// Layout of the contracts deployed before the version record, they have a
+/// single nft contract and string burn types
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct StateV1 {
+    pub owner: Addr,
+    pub is_active: bool,
+    pub nft_contract: ContractInfo,
+    pub reward_contracts: Vec<RewardsContractInfoV1>,
+    pub viewing_key: Option<String>,
+    pub total_burned_amount: u32,
+    pub trait_restriction: Option<String>,
+    pub burn_counter_date: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RewardsContractInfoV1 {
+    pub code_hash: String,
+    pub address: Addr,
+    pub base_reward: Uint128,
+    pub bonus_hourly: Uint128,
+    pub name: String,
+    pub burn_type: String,
+    pub total_rewards: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct HistoryFullV1 {
+    pub token_id: String,
+    pub message: String,
+    pub date: u64,
+    pub meta_data: Metadata,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct HistoryV1 {
+    pub token_id: String,
+    pub message: String,
+    pub date: u64,
+    pub rewards: Uint128,
+}
+
+/// Rank of the v1 layout, its per token rewards are replaced by the rank tiers
+/// of the migration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RankV1 {
+    pub token_id: String,
+    pub rank: u32,
+    pub rank_rewards: Vec<RankRewardV1>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RankRewardV1 {
+    pub rank_reward: Uint128,
+    pub reward_contract_name: String,
+}
+
+static CONFIG_ITEM_V1: Item<StateV1> = Item::new(CONFIG_KEY);
+static BURN_HISTORY_STORE_V1: AppendStore<HistoryFullV1> = AppendStore::new(BURN_HISTORY_KEY);
+static HISTORY_STORE_V1: AppendStore<HistoryV1> = AppendStore::new(HISTORY_KEY);
+/// nft contract of the v1 histories and ranks, only saved by migrated contracts
+static HISTORY_V1_CONTRACT_ITEM: Item<Addr> = Item::new(HISTORY_V1_CONTRACT_KEY);
+
+pub fn state_from_v1(old: StateV1, entropy: &str) -> State {
+    State {
+        owner: old.owner,
+        is_active: old.is_active,
+        nft_contracts: vec![NftCollection {
+            contract: old.nft_contract,
+            trait_rules: None,
+            reward_multipliers: vec![],
+            upgrade: None,
+        }],
+        reward_contracts: old
+            .reward_contracts
+            .into_iter()
+            .map(reward_contract_from_v1)
+            .collect(),
+        viewing_key: old.viewing_key,
+        total_burned_amount: old.total_burned_amount,
+        trait_rules: old
+            .trait_restriction
+            .map(|trait_type| TraitRule::HasTrait { trait_type }),
+        burn_counter_date: old.burn_counter_date,
+        start_time: None,
+        end_time: None,
+        caps: Default::default(),
+        prng_seed: sha_256(entropy.as_bytes()).to_vec(),
+        mint_reward: None,
+        recipes: vec![],
+        pending_owner: None,
+        roles: vec![],
+    }
+}
+
+fn reward_contract_from_v1(old: RewardsContractInfoV1) -> RewardsContractInfo {
+    RewardsContractInfo {
+        code_hash: old.code_hash,
+        address: old.address,
+        base_reward: old.base_reward,
+        bonus_hourly: old.bonus_hourly,
+        name: old.name,
+        burn_type: match old.burn_type.as_str() {
+            "rank" => RewardStrategy::Rank,
+            _ => RewardStrategy::Flat,
+        },
+        total_rewards: old.total_rewards,
+        bonus_curve: None,
+        bonus_reset: None,
+        vesting: None,
+        lottery: None,
+        denom: None,
+    }
+}
+
+pub fn full_history_from_v1(old: HistoryFullV1, nft_contract: &Addr) -> HistoryFull {
+    HistoryFull {
+        token_id: old.token_id,
+        nft_contract: nft_contract.clone(),
+        message: old.message,
+        date: old.date,
+        meta_data: old.meta_data,
+        upgrade: None,
+    }
+}
+
+pub fn history_from_v1(old: HistoryV1, nft_contract: &Addr) -> History {
+    History {
+        token_id: old.token_id,
+        nft_contract: nft_contract.clone(),
+        message: old.message,
+        date: old.date,
+        rewards: old.rewards,
+        lottery_rolls: vec![],
+        minted_token_id: None,
+        recipe: None,
+    }
+}
+
+/// Nft contract the v1 layout was deployed for, `None` unless migrated from it
+pub fn v1_nft_contract(storage: &dyn Storage) -> StdResult<Option<Addr>> {
+    HISTORY_V1_CONTRACT_ITEM.may_load(storage)
+}
+
+/// Ranks of the v1 nft contract, read as its first rank generation
+pub fn v1_rank_store() -> Keymap<'static, String, RankV1> {
+    Keymap::new(RANK_KEY)
+}
+
+/// Number of history entries of a wallet, its v1 entries included
+pub fn user_history_len(storage: &dyn Storage, wallet: &Addr) -> StdResult<u32> {
+    let suffix = wallet.as_str().as_bytes();
+    let v1_len = if HISTORY_V1_CONTRACT_ITEM.may_load(storage)?.is_some() {
+        HISTORY_STORE_V1.add_suffix(suffix).get_len(storage)?
+    } else {
+        0
+    };
+    Ok(v1_len + HISTORY_STORE.add_suffix(suffix).get_len(storage)?)
+}
+
+/// Page of a wallet's history, the v1 entries come first
+pub fn user_history(
+    storage: &dyn Storage,
+    wallet: &Addr,
+    start_page: u32,
+    page_size: u32,
+) -> StdResult<Vec<History>> {
+    let suffix = wallet.as_str().as_bytes();
+    let v1_contract = HISTORY_V1_CONTRACT_ITEM.may_load(storage)?;
+    let v1_store = HISTORY_STORE_V1.add_suffix(suffix);
+    let v1 = v1_contract
+        .as_ref()
+        .map(|nft_contract| (&v1_store, move |x| history_from_v1(x, nft_contract)));
+    history_page(
+        storage,
+        v1,
+        &HISTORY_STORE.add_suffix(suffix),
+        start_page,
+        page_size,
+    )
+}
+
+/// Number of entries of the full burn history, its v1 entries included
+pub fn full_history_len(storage: &dyn Storage) -> StdResult<u32> {
+    let v1_len = if HISTORY_V1_CONTRACT_ITEM.may_load(storage)?.is_some() {
+        BURN_HISTORY_STORE_V1.get_len(storage)?
+    } else {
+        0
+    };
+    Ok(v1_len + BURN_HISTORY_STORE.get_len(storage)?)
+}
+
+/// Page of the full burn history, the v1 entries come first
+pub fn full_history(
+    storage: &dyn Storage,
+    start_page: u32,
+    page_size: u32,
+) -> StdResult<Vec<HistoryFull>> {
+    let v1_contract = HISTORY_V1_CONTRACT_ITEM.may_load(storage)?;
+    let v1 = v1_contract.as_ref().map(|nft_contract| {
+        (&BURN_HISTORY_STORE_V1, move |x| {
+            full_history_from_v1(x, nft_contract)
+        })
+    });
+    history_page(storage, v1, &BURN_HISTORY_STORE, start_page, page_size)
+}
+
+/// Page of a history continuing an append store of the v1 layout, whose
+/// entries are converted as they are read
+fn history_page<V1, T, F>(
+    storage: &dyn Storage,
+    v1: Option<(&AppendStore<V1>, F)>,
+    store: &AppendStore<T>,
+    start_page: u32,
+    page_size: u32,
+) -> StdResult<Vec<T>>
+where
+    V1: Serialize + DeserializeOwned,
+    T: Serialize + DeserializeOwned,
+    F: Fn(V1) -> T,
+{
+    let v1_len = match v1.as_ref() {
+        Some((v1_store, _)) => v1_store.get_len(storage)?,
+        None => 0,
+    };
+    let len = v1_len + store.get_len(storage)?;
+
+    let start = start_page.saturating_mul(page_size).min(len);
+    let end = start.saturating_add(page_size).min(len);
+    let mut history = Vec::new();
+    for i in start..end {
+        match v1.as_ref() {
+            Some((v1_store, from_v1)) if i < v1_len => {
+                history.push(from_v1(v1_store.get_at(storage, i)?))
+            }
+            _ => history.push(store.get_at(storage, i - v1_len)?),
+        }
+    }
+    Ok(history)
+}
+
+/// Rewrites the config in the current layout. The histories stay under their
+/// v1 keys and are converted as `user_history` and `full_history` read them,
+/// the v1 ranks stay as the first rank generation of the v1 nft contract, their
+/// rewards are paid through the rank tiers of the migration
+fn migrate_v1(storage: &mut dyn Storage, msg: &MigrateMsg) -> Result<(), ContractError> {
+    let old = CONFIG_ITEM_V1.load(storage)?;
+    let nft_contract = old.nft_contract.address.clone();
+    let state = state_from_v1(old, &msg.entropy);
+    if let Some(rank_tiers) = msg.rank_tiers.as_ref() {
+        validate_rank_tiers(rank_tiers, &state.reward_contracts)?;
+        RANK_TIERS_ITEM.save(storage, rank_tiers)?;
+    }
+
+    COLLECTION_BURNS_STORE.insert(storage, &nft_contract, &state.total_burned_amount)?;
+    HISTORY_V1_CONTRACT_ITEM.save(storage, &nft_contract)?;
+    CONFIG_ITEM.save(storage, &state)?;
+    Ok(())
+}
+
+/// Brings the stored layout up to `STATE_VERSION`, contracts without a version
+/// record are on the v1 layout
+pub fn migrate_storage(storage: &mut dyn Storage, msg: &MigrateMsg) -> Result<(), ContractError> {
+    let state_version = match CONTRACT_VERSION_ITEM.may_load(storage)? {
+        Some(version) => version.state_version,
+        None => 1,
+    };
+    if state_version < 2 {
+        migrate_v1(storage, msg)?;
+    }
+    save_contract_version(storage)?;
+    Ok(())
+}
+
+pub fn save_contract_version(storage: &mut dyn Storage) -> StdResult<()> {
+    CONTRACT_VERSION_ITEM.save(
+        storage,
+        &ContractVersion {
+            contract: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
+            state_version: STATE_VERSION,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{migrate, query};
+    use crate::msg::{QueryMsg, Rank, RankResponse, RankTier, TierEffect, TierReward};
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+
+    #[test]
+    fn v1_state_migrates() {
+        let mut deps = mock_dependencies();
+        let nft_contract = ContractInfo {
+            code_hash: "hash".to_string(),
+            address: Addr::unchecked("nft"),
+            name: "nft".to_string(),
+        };
+        let old = StateV1 {
+            owner: Addr::unchecked("owner"),
+            is_active: true,
+            nft_contract: nft_contract.clone(),
+            reward_contracts: vec![RewardsContractInfoV1 {
+                code_hash: "hash".to_string(),
+                address: Addr::unchecked("reward"),
+                base_reward: Uint128::from(10u128),
+                bonus_hourly: Uint128::from(1u128),
+                name: "shill".to_string(),
+                burn_type: "rank".to_string(),
+                total_rewards: Uint128::from(1000u128),
+            }],
+            viewing_key: Some("key".to_string()),
+            total_burned_amount: 7,
+            trait_restriction: Some("Head".to_string()),
+            burn_counter_date: 100,
+        };
+        CONFIG_ITEM_V1.save(&mut deps.storage, &old).unwrap();
+        let wallet = Addr::unchecked("wallet");
+        HISTORY_STORE_V1
+            .add_suffix(wallet.as_str().as_bytes())
+            .push(
+                &mut deps.storage,
+                &HistoryV1 {
+                    token_id: "1".to_string(),
+                    message: "gone".to_string(),
+                    date: 100,
+                    rewards: Uint128::from(10u128),
+                },
+            )
+            .unwrap();
+        BURN_HISTORY_STORE_V1
+            .push(
+                &mut deps.storage,
+                &HistoryFullV1 {
+                    token_id: "1".to_string(),
+                    message: "gone".to_string(),
+                    date: 100,
+                    meta_data: Metadata {
+                        token_uri: None,
+                        extension: None,
+                    },
+                },
+            )
+            .unwrap();
+
+        v1_rank_store()
+            .insert(
+                &mut deps.storage,
+                &"5".to_string(),
+                &RankV1 {
+                    token_id: "5".to_string(),
+                    rank: 3,
+                    rank_rewards: vec![RankRewardV1 {
+                        rank_reward: Uint128::from(7u128),
+                        reward_contract_name: "shill".to_string(),
+                    }],
+                },
+            )
+            .unwrap();
+
+        let rank_tiers = vec![RankTier {
+            min_rank: 1,
+            max_rank: 10,
+            rewards: vec![TierReward {
+                reward_contract_name: "shill".to_string(),
+                effect: TierEffect::Amount {
+                    amount: Uint128::from(7u128),
+                },
+            }],
+        }];
+        let mut msg = MigrateMsg {
+            entropy: "entropy".to_string(),
+            rank_tiers: Some(rank_tiers.clone()),
+        };
+        msg.rank_tiers.as_mut().unwrap()[0].rewards[0].reward_contract_name = "gold".to_string();
+        assert!(migrate(deps.as_mut(), mock_env(), msg.clone()).is_err());
+        msg.rank_tiers = Some(rank_tiers.clone());
+        migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+        assert_eq!(RANK_TIERS_ITEM.load(&deps.storage).unwrap(), rank_tiers);
+
+        let state = CONFIG_ITEM.load(&deps.storage).unwrap();
+        assert_eq!(state.nft_contracts[0].contract, nft_contract);
+        assert_eq!(state.reward_contracts[0].burn_type, RewardStrategy::Rank);
+        assert_eq!(
+            state.trait_rules,
+            Some(TraitRule::HasTrait {
+                trait_type: "Head".to_string()
+            })
+        );
+        assert_eq!(state.burn_counter_date, 100);
+        // the v1 full history is converted as it is read, before later burns
+        let full_after = HistoryFull {
+            token_id: "2".to_string(),
+            nft_contract: nft_contract.address.clone(),
+            message: "".to_string(),
+            date: 200,
+            meta_data: Metadata {
+                token_uri: None,
+                extension: None,
+            },
+            upgrade: None,
+        };
+        BURN_HISTORY_STORE
+            .push(&mut deps.storage, &full_after)
+            .unwrap();
+        assert_eq!(full_history_len(&deps.storage).unwrap(), 2);
+        let history = full_history(&deps.storage, 0, 10).unwrap();
+        assert_eq!(history[0].token_id, "1");
+        assert_eq!(history[0].nft_contract, nft_contract.address);
+        assert_eq!(history[1], full_after);
+        assert_eq!(
+            COLLECTION_BURNS_STORE.get(&deps.storage, &nft_contract.address),
+            Some(7)
+        );
+        // the wallet's v1 entry reads next to the ones burned after the migration
+        let burned_after = History {
+            token_id: "2".to_string(),
+            nft_contract: nft_contract.address.clone(),
+            message: "".to_string(),
+            date: 200,
+            rewards: Uint128::from(10u128),
+            lottery_rolls: vec![],
+            minted_token_id: None,
+            recipe: None,
+        };
+        HISTORY_STORE
+            .add_suffix(wallet.as_str().as_bytes())
+            .push(&mut deps.storage, &burned_after)
+            .unwrap();
+        assert_eq!(user_history_len(&deps.storage, &wallet).unwrap(), 2);
+        let history = user_history(&deps.storage, &wallet, 0, 10).unwrap();
+        assert_eq!(history[0].token_id, "1");
+        assert_eq!(history[0].nft_contract, nft_contract.address);
+        assert_eq!(history[1], burned_after);
+        assert_eq!(
+            user_history(&deps.storage, &wallet, 1, 1).unwrap(),
+            vec![burned_after]
+        );
+
+        // the v1 ranks are the ranks of the v1 nft contract, without their rewards
+        let response: RankResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetRank {
+                    nft_contract: Some(nft_contract.address.clone()),
+                    token_id: "5".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            response.rank,
+            Some(Rank {
+                token_id: "5".to_string(),
+                rank: 3,
+            })
+        );
+
+        let version = CONTRACT_VERSION_ITEM.load(&deps.storage).unwrap();
+        assert_eq!(version.state_version, STATE_VERSION);
+
+        // a second migration keeps the current layout untouched
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(CONFIG_ITEM.load(&deps.storage).unwrap(), state);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::error::ContractError;
+use crate::msg::MintReward;
+use crate::state::MINTED_COUNTS_STORE;
+use crate::traits::token_attributes;
+use cosmwasm_std::{Addr, StdResult, Storage};
+use secret_toolkit::snip721::{Extension, Metadata, Trait};
+
+pub fn validate_mint_reward(mint_reward: &MintReward) -> Result<(), ContractError> {
+    if mint_reward.burn_ratio == 0 {
+        return Err(ContractError::InvalidConfig {
+            reason: "Mint reward burn ratio cannot be 0".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Number of tokens minted for `burned` tokens, the burn has to be a multiple
+/// of the ratio so no token is burned without its share of a mint
+pub fn minted_count(mint_reward: &MintReward, burned: usize) -> Result<usize, ContractError> {
+    let ratio = mint_reward.burn_ratio as usize;
+    if burned % ratio != 0 {
+        return Err(ContractError::BurnMultiple {
+            multiple: mint_reward.burn_ratio,
+        });
+    }
+    Ok(burned / ratio)
+}
+
+fn minted_key(mint_reward: &MintReward) -> (Addr, String) {
+    (
+        mint_reward.contract.address.clone(),
+        mint_reward.token_id_prefix.clone(),
+    )
+}
+
+/// Tokens minted so far with the contract and token id prefix of `mint_reward`
+pub fn minted_amount(storage: &dyn Storage, mint_reward: &MintReward) -> u32 {
+    MINTED_COUNTS_STORE
+        .get(storage, &minted_key(mint_reward))
+        .unwrap_or_default()
+}
+
+pub fn add_minted(
+    storage: &mut dyn Storage,
+    mint_reward: &MintReward,
+    count: u32,
+) -> StdResult<()> {
+    let minted = minted_amount(storage, mint_reward) + count;
+    MINTED_COUNTS_STORE.insert(storage, &minted_key(mint_reward), &minted)
+}
+
+pub fn minted_token_id(mint_reward: &MintReward, number: u32) -> String {
+    format!("{}{}", mint_reward.token_id_prefix, number)
+}
+
+/// Public metadata of a minted token, the template plus the carried traits of
+/// the tokens burned for it
+pub fn minted_metadata(mint_reward: &MintReward, burned: &[Option<&Metadata>]) -> Metadata {
+    let mut metadata = mint_reward.public_metadata.clone();
+    let mut carried: Vec<Trait> = Vec::new();
+    for trait_type in mint_reward.carry_traits.iter() {
+        if let Some(attribute) = burned.iter().find_map(|x| {
+            token_attributes(*x)
+                .iter()
+                .find(|y| y.trait_type.as_deref() == Some(trait_type.as_str()))
+        }) {
+            carried.push(attribute.clone());
+        }
+    }
+    if !carried.is_empty() {
+        metadata
+            .extension
+            .get_or_insert_with(Extension::default)
+            .attributes
+            .get_or_insert_with(Vec::new)
+            .extend(carried);
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::ContractInfo;
+    use crate::testing::with_attributes;
+    use cosmwasm_std::testing::mock_dependencies;
+
+    #[test]
+    fn minted_token_carries_traits() {
+        let mint_reward = MintReward {
+            contract: ContractInfo {
+                code_hash: "".to_string(),
+                address: Addr::unchecked("gen2"),
+                name: "gen2".to_string(),
+            },
+            burn_ratio: 3,
+            token_id_prefix: "gen2-".to_string(),
+            public_metadata: with_attributes(vec![("Generation", "2")]),
+            private_metadata: None,
+            carry_traits: vec!["Background".to_string(), "Hat".to_string()],
+        };
+        assert_eq!(minted_count(&mint_reward, 6).unwrap(), 2);
+        assert!(minted_count(&mint_reward, 4).is_err());
+        assert_eq!(minted_token_id(&mint_reward, 7), "gen2-7");
+
+        let first = with_attributes(vec![("Eyes", "Laser")]);
+        let second = with_attributes(vec![("Background", "Gold")]);
+        let minted = minted_metadata(&mint_reward, &[Some(&first), Some(&second), None]);
+        assert_eq!(
+            minted,
+            with_attributes(vec![("Generation", "2"), ("Background", "Gold")])
+        );
+    }
+
+    #[test]
+    fn minted_ids_count_per_contract_and_prefix() {
+        let mut deps = mock_dependencies();
+        let gen2 = MintReward {
+            contract: ContractInfo {
+                code_hash: "".to_string(),
+                address: Addr::unchecked("gen2"),
+                name: "gen2".to_string(),
+            },
+            burn_ratio: 1,
+            token_id_prefix: "gen2-".to_string(),
+            public_metadata: with_attributes(vec![]),
+            private_metadata: None,
+            carry_traits: vec![],
+        };
+        add_minted(&mut deps.storage, &gen2, 3).unwrap();
+        assert_eq!(minted_amount(&deps.storage, &gen2), 3);
+
+        // a new prefix or contract numbers its tokens from 1 again
+        let prefix = MintReward {
+            token_id_prefix: "gold-".to_string(),
+            ..gen2.clone()
+        };
+        let mut contract = gen2.clone();
+        contract.contract.address = Addr::unchecked("gen3");
+        assert_eq!(minted_amount(&deps.storage, &prefix), 0);
+        assert_eq!(minted_amount(&deps.storage, &contract), 0);
+        add_minted(&mut deps.storage, &prefix, 1).unwrap();
+        assert_eq!(minted_amount(&deps.storage, &prefix), 1);
+        assert_eq!(minted_amount(&deps.storage, &gen2), 3);
+    }
+}
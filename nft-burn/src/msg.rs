@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, Binary, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
 use schemars::JsonSchema;
 use secret_toolkit::{
     permit::Permit,
@@ -9,10 +9,24 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct InstantiateMsg {
     pub entropy: String,
-    pub nft_contract: ContractInfo,
+    pub nft_contracts: Vec<NftCollection>,
     pub reward_contracts: Vec<RewardsContractInfo>,
-    pub trait_restriction: Option<String>,
+    pub trait_rules: Option<TraitRule>,
+    /// ranks of the first nft contract
     pub ranks: Vec<Rank>,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub caps: Option<BurnCaps>,
+    pub mint_reward: Option<MintReward>,
+    pub recipes: Vec<Recipe>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MigrateMsg {
+    /// seeds the lottery rng of a contract migrated from the v1 layout
+    pub entropy: String,
+    /// rank tiers replacing the per token rank rewards of the v1 layout
+    pub rank_tiers: Option<Vec<RankTier>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -22,6 +36,92 @@ pub struct ContractInfo {
     pub name: String,
 }
 
+/// NFT minted to the burner for every `burn_ratio` burned tokens, this contract
+/// has to be a minter of `contract`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MintReward {
+    pub contract: ContractInfo,
+    pub burn_ratio: u32,
+    /// minted token ids are the prefix followed by a running number, counted
+    /// per mint contract and prefix
+    pub token_id_prefix: String,
+    pub public_metadata: Metadata,
+    pub private_metadata: Option<Metadata>,
+    /// trait types copied into the public metadata from the first burned token
+    /// that has them
+    pub carry_traits: Vec<String>,
+}
+
+/// Set of tokens that pays `rewards` when burned together, the first recipe
+/// matched by a burn is used. A recipe takes up to 4 ingredients and 8 tokens
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Recipe {
+    pub name: String,
+    /// every burned token has to be used by exactly one ingredient
+    pub ingredients: Vec<RecipeIngredient>,
+    pub rewards: Vec<RecipeReward>,
+    /// per-token base and rank rewards are not paid when the recipe matches
+    pub replaces_token_rewards: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeIngredient {
+    /// `count` tokens satisfying the rule
+    Matching { rule: TraitRule, count: u32 },
+    /// `count` tokens sharing the same value of this trait
+    SameTrait { trait_type: String, count: u32 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RecipeReward {
+    pub reward_contract_name: String,
+    pub amount: Uint128,
+}
+
+/// NFT contract whose tokens can be burned
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NftCollection {
+    pub contract: ContractInfo,
+    /// checked in addition to the contract wide trait rules
+    pub trait_rules: Option<TraitRule>,
+    /// multiplies the base reward of this collection's tokens, reward contracts
+    /// that are not listed pay their base reward unchanged
+    pub reward_multipliers: Vec<RewardMultiplier>,
+    /// lets holders burn tokens of this collection to upgrade one they keep,
+    /// this contract has to be a minter allowed to update metadata
+    pub upgrade: Option<UpgradeConfig>,
+}
+
+/// This contract has to be allowed to set the metadata of the collection's tokens
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct UpgradeConfig {
+    /// tokens burned per upgrade
+    pub burn_count: u32,
+    /// applied in order to the kept token's public metadata
+    pub rules: Vec<UpgradeRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeRule {
+    /// adds `amount` to a numeric trait, missing traits start at 0, the
+    /// upgrade fails once the trait reached `max`
+    IncrementTrait {
+        trait_type: String,
+        amount: u32,
+        max: Option<u32>,
+    },
+    /// adds the trait or replaces its value
+    SetTrait { trait_type: String, value: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RewardMultiplier {
+    pub reward_contract_name: String,
+    pub multiplier: Decimal,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct RewardsContractInfo {
     pub code_hash: String,
@@ -29,31 +129,235 @@ pub struct RewardsContractInfo {
     pub base_reward: Uint128,
     pub bonus_hourly: Uint128,
     pub name: String,
-    pub burn_type: String,
+    pub burn_type: RewardStrategy,
     pub total_rewards: Uint128,
+    /// how the bonus grows with the hours since the burn counter date, `None`
+    /// keeps the uncapped `bonus_hourly` per hour
+    pub bonus_curve: Option<BonusCurve>,
+    /// when this contract's bonus clock restarts, `None` restarts it after it
+    /// paid out a bonus
+    pub bonus_reset: Option<BonusReset>,
+    /// rewards are locked in the vesting ledger instead of being paid on
+    /// burn, `None` pays them immediately
+    pub vesting: Option<VestingSchedule>,
+    /// every burned token rolls for one of these prizes, which replaces its
+    /// base reward before the expected reward check
+    pub lottery: Option<Vec<LotteryPrize>>,
+    /// pays this native denom through the bank module instead of the SNIP-20
+    /// at `address`, funded with `FundNative`
+    pub denom: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LotteryPrize {
+    /// chance of the prize relative to the total weight of the table
+    pub weight: u32,
+    pub outcome: LotteryOutcome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LotteryOutcome {
+    /// multiplies the token's base reward
+    Multiplier { multiplier: Decimal },
+    /// pays this amount instead of the token's base reward
+    Jackpot { amount: Uint128 },
+}
+
+/// Rewards of a claim are released linearly over `duration_seconds`, nothing
+/// is released before `cliff_seconds` have passed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingSchedule {
+    pub cliff_seconds: u64,
+    pub duration_seconds: u64,
+}
+
+/// How the per-token reward of a reward contract is calculated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardStrategy {
+    /// every token pays `base_reward`
+    Flat,
+    /// `base_reward` plus the rank tier of the token's rank
+    Rank,
+    /// `base_reward` adjusted by the effect of every trait the token has
+    TraitWeighted { weights: Vec<TraitWeight> },
+    /// reward of the highest tier reached by the number of tokens burned so far
+    BurnCountTiers { tiers: Vec<BurnCountTier> },
+    /// `base_reward` moved along a curve of the number of tokens burned so far
+    Curve { curve: BurnCurve },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TraitWeight {
+    pub trait_type: String,
+    pub value: String,
+    pub effect: TraitEffect,
+}
+
+/// Amounts are added to the base reward first, the sum is then multiplied
+/// by every matching multiplier
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitEffect {
+    Add { amount: Uint128 },
+    Multiply { multiplier: Decimal },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnCountTier {
+    pub min_burned: u32,
+    pub reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnCurve {
+    /// decreases by `decrement` per burned token, never going below `floor`
+    Linear { decrement: Uint128, floor: Uint128 },
+    /// halves every `epoch_burns` burned tokens, never going below `floor`
+    Halving { epoch_burns: u32, floor: Uint128 },
+    /// interpolated between the points, the first one at 0 burns, and the
+    /// reward of the last point after it
+    Piecewise { points: Vec<CurvePoint> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CurvePoint {
+    pub burned: u32,
+    pub reward: Uint128,
+}
+
+/// Bonus accrued after a number of whole hours without a burn
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BonusCurve {
+    /// `bonus_hourly` per hour, never above `cap`
+    LinearCapped { cap: Uint128 },
+    /// bonus of the last step reached, zero before the first step
+    Step { steps: Vec<BonusStep> },
+    /// approaches `max`, closing half of the remaining gap every `half_life_hours`
+    Exponential { max: Uint128, half_life_hours: u64 },
+    /// `bonus_hourly` per hour, dropping back to zero every `period_hours`
+    DutchAuction { period_hours: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BonusReset {
+    /// restarts after a claim paid out a bonus of this contract
+    OnBonusPayout,
+    /// restarts after every claim paying out rewards of this contract
+    OnAnyPayout,
+    /// only restarts through `ResetBurnCounterDate`
+    Manual,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BonusStep {
+    pub after_hours: u64,
+    pub bonus: Uint128,
+}
+
+/// Requirement a token's public metadata attributes must satisfy to be burned
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitRule {
+    /// token has an attribute of this trait type, whatever its value
+    HasTrait {
+        trait_type: String,
+    },
+    /// token has an attribute of this trait type with exactly this value
+    Equals {
+        trait_type: String,
+        value: String,
+    },
+    /// token has an attribute of this trait type with one of these values
+    OneOf {
+        trait_type: String,
+        values: Vec<String>,
+    },
+    And {
+        rules: Vec<TraitRule>,
+    },
+    Or {
+        rules: Vec<TraitRule>,
+    },
+    Not {
+        rule: Box<TraitRule>,
+    },
+}
+
+/// Limits on the number of burned tokens, `None` means unlimited
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct BurnCaps {
+    pub max_per_tx: Option<u32>,
+    /// lifetime limit per wallet
+    pub max_per_wallet: Option<u32>,
+    pub max_per_period: Option<PeriodCap>,
+    /// limit on the total burned by everyone
+    pub max_total: Option<u32>,
+}
+
+/// Limits the burns of a wallet in any window of `period_seconds`, each burn
+/// counts until `period_seconds` after it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PeriodCap {
+    pub max_burns: u32,
+    pub period_seconds: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct History {
     pub token_id: String,
+    pub nft_contract: Addr,
     pub message: String,
     pub date: u64,
     pub rewards: Uint128,
+    pub lottery_rolls: Vec<LotteryRoll>,
+    /// token minted for the group of burned tokens this one belongs to
+    pub minted_token_id: Option<String>,
+    /// recipe matched by the burn, its rewards are in the first token's entry
+    pub recipe: Option<String>,
+}
+
+/// `prize_index` is the entry of the reward contract's lottery table hit by
+/// `roll`, see `GetBurnInfo` for the table. `roll` is the `draw`th roll of
+/// `Prng::new(seed, entropy)`, the seed hashes to the `lottery_commitment` shown
+/// before the claim
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LotteryRoll {
+    pub reward_contract_name: String,
+    pub roll: u64,
+    pub prize_index: u32,
+    pub reward: Uint128,
+    pub seed: Binary,
+    pub entropy: Binary,
+    pub draw: u32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct HistoryFull {
     pub token_id: String,
+    pub nft_contract: Addr,
     pub message: String,
     pub date: u64,
     pub meta_data: Metadata,
+    /// token upgraded by this burn
+    pub upgrade: Option<TokenUpgrade>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TokenUpgrade {
+    pub token_id: String,
+    pub before: Metadata,
+    pub after: Metadata,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Rank {
     pub token_id: String,
     pub rank: u32,
-    pub rank_rewards: Vec<RankReward>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -76,17 +380,113 @@ pub enum ExecuteMsg {
     UpdateRewardContract {
         contracts: Vec<RewardsContractInfo>,
     },
+    /// the rank messages and queries act on the ranks of `nft_contract`, the
+    /// first nft contract when it is `None`
     UpdateRanks {
+        nft_contract: Option<Addr>,
         ranks: Vec<Rank>,
     },
+    RemoveRanks {
+        nft_contract: Option<Addr>,
+        token_ids: Vec<String>,
+    },
+    /// removes up to `limit` ranks, the remaining count is returned in the
+    /// `remaining_ranks` attribute
+    ClearRanks {
+        nft_contract: Option<Addr>,
+        limit: u32,
+    },
+    /// inserts ranks packed in the binary layout described on `decode_ranks`
+    ImportRanks {
+        nft_contract: Option<Addr>,
+        data: Binary,
+    },
+    /// merkle root of the collection's rank leaves, see `leaf_hash` for their
+    /// layout
+    SetRankRoot {
+        nft_contract: Option<Addr>,
+        root: Option<Binary>,
+    },
+    SetRankTiers {
+        tiers: Vec<RankTier>,
+    },
+    /// starts a new rarity scan of a collection, dropping the unfinished one,
+    /// the collection's ranks are replaced once the scan is done, uploaded
+    /// ranks included. A scan that finds no tokens can't finish
+    StartRarity {
+        nft_contract: Option<Addr>,
+        method: RarityMethod,
+    },
+    /// advances the rarity scan by up to `limit` tokens
+    CrankRarity {
+        limit: u32,
+    },
+    /// `None` removes the collection's scarcity bonus, its burn counters are kept
+    SetScarcityBonus {
+        nft_contract: Addr,
+        bonus: Option<ScarcityBonus>,
+    },
     RemoveRewards {},
-    ResetBurnCounterDate {},
+    /// restarts the bonus clock of one reward contract, or of all of them
+    ResetBurnCounterDate {
+        reward_contract_name: Option<String>,
+    },
     SetViewingKey {
         key: String,
     },
     SetActiveState {
         is_active: bool,
     },
+    SetTraitRules {
+        trait_rules: Option<TraitRule>,
+    },
+    UpdateTraitWeights {
+        reward_contract_name: String,
+        weights: Vec<TraitWeight>,
+    },
+    AddNftContract {
+        collection: NftCollection,
+    },
+    RemoveNftContract {
+        address: Addr,
+    },
+    SetSchedule {
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    },
+    SweepRewards {
+        treasury: Addr,
+    },
+    SetBurnCaps {
+        caps: BurnCaps,
+    },
+    /// pays out the released part of the sender's vesting rewards
+    ClaimVested {},
+    /// adds the sent coins to the rewards of the native reward entries
+    FundNative {},
+    SetMintReward {
+        mint_reward: Option<MintReward>,
+    },
+    AddRecipe {
+        recipe: Recipe,
+    },
+    RemoveRecipe {
+        name: String,
+    },
+    GrantRole {
+        role: Role,
+        address: Addr,
+    },
+    RevokeRole {
+        role: Role,
+        address: Addr,
+    },
+    /// starts a transfer of ownership, `None` cancels a pending one
+    ProposeOwner {
+        new_owner: Option<Addr>,
+    },
+    /// completes the transfer, sent by the proposed owner
+    AcceptOwnership {},
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -101,6 +501,21 @@ pub enum HandleNftReceiveMsg {
     ClaimBurnRewards {
         expected_rewards: Vec<ExpectedCheck>,
         message: String,
+        /// ranks of burned tokens proven against the rank root, they take
+        /// precedence over stored ranks
+        rank_proofs: Option<Vec<RankProof>>,
+        /// the claim is rejected once the deadline passed
+        deadline: Option<ClaimDeadline>,
+        token_minimums: Option<Vec<TokenMinimum>>,
+        /// rejects the claim unless every reward contract is in `expected_rewards`
+        require_all_rewards: Option<bool>,
+    },
+    /// burns the sent tokens to upgrade `keep_token_id`, the viewing key is
+    /// the sender's and proves they own the kept token
+    UpgradeToken {
+        keep_token_id: String,
+        viewing_key: String,
+        message: String,
     },
 }
 
@@ -110,10 +525,27 @@ pub enum QueryMsg {
     GetBurnInfo {},
     GetExpectedRewards {
         token_ids: Vec<String>,
+        nft_contract: Option<Addr>,
+        rank_proofs: Option<Vec<RankProof>>,
+    },
+    CheckTraitRules {
+        token_ids: Vec<String>,
+        nft_contract: Option<Addr>,
+    },
+    /// recipe a burn of these tokens would match
+    MatchRecipe {
+        token_ids: Vec<String>,
+        nft_contract: Option<Addr>,
     },
     GetRewardBalance {
         viewer: ViewerInfo,
     },
+    GetBurnAllowance {
+        permit: Permit,
+    },
+    GetVesting {
+        permit: Permit,
+    },
     GetNumUserHistory {
         permit: Permit,
     },
@@ -127,16 +559,63 @@ pub enum QueryMsg {
         start_page: u32,
         page_size: u32,
     },
+    GetRoles {},
+    GetRank {
+        nft_contract: Option<Addr>,
+        token_id: String,
+    },
+    GetRanks {
+        nft_contract: Option<Addr>,
+        start_page: u32,
+        page_size: u32,
+    },
+    GetRankTiers {},
+    GetRarityStatus {},
+    /// runs the claim of burning `token_ids` from `owner` without executing it
+    SimulateClaim {
+        token_ids: Vec<String>,
+        owner: Addr,
+        nft_contract: Option<Addr>,
+        rank_proofs: Option<Vec<RankProof>>,
+        deadline: Option<ClaimDeadline>,
+    },
+    /// burned and remaining supply of the collection's configured trait values
+    GetScarcity {
+        nft_contract: Option<Addr>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct BurnInfoResponse {
     pub total_burned_amount: u32,
-    pub nft_contract: ContractInfo,
+    pub nft_contracts: Vec<CollectionBurnInfo>,
     pub reward_contracts: Vec<RewardsContractInfo>,
-    pub trait_restriction: Option<String>,
+    pub trait_rules: Option<TraitRule>,
     pub is_active: bool,
     pub burn_counter_date: u64,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub status: CampaignStatus,
+    pub mint_reward: Option<MintReward>,
+    /// tokens minted with the contract and token id prefix of `mint_reward`
+    pub minted_amount: u32,
+    pub recipes: Vec<Recipe>,
+    /// sha256 of the seed the next claim rolls the lotteries with
+    pub lottery_commitment: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignStatus {
+    Scheduled,
+    Running,
+    Ended,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CollectionBurnInfo {
+    pub collection: NftCollection,
+    pub total_burned_amount: u32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -148,6 +627,8 @@ pub struct ExpectedReward {
     pub token_id: String,
     pub rank: Option<u32>,
     pub reward_contract_name: String,
+    /// time the bonus started accruing from
+    pub bonus_start: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -157,16 +638,269 @@ pub struct ExpectedCheck {
     pub reward_contract_name: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimDeadline {
+    /// last block height the claim executes at
+    AtHeight { height: u64 },
+    /// last block time in seconds the claim executes at
+    AtTime { time: u64 },
+}
+
+/// Lower bound of the base and rank reward paid for one burned token, after
+/// the lottery and recipes. Recipe rewards are paid for the whole burn and do
+/// not count, a recipe replacing the token rewards pays nothing per token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TokenMinimum {
+    pub token_id: String,
+    pub reward_contract_name: String,
+    pub min_reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulateClaimResponse {
+    /// to be sent as the `expected_rewards` of `ClaimBurnRewards`
+    pub expected_rewards: Vec<ExpectedCheck>,
+    /// lotteries are not rolled, their reward contracts are paid the lowest
+    /// prize here
+    pub payouts: Vec<ClaimPayout>,
+    /// rewards the claim's lottery rolls can pay, the rolls are only known once
+    /// the claim runs
+    pub lottery_ranges: Vec<LotteryRange>,
+    pub recipe: Option<String>,
+    /// error the claim would fail with
+    pub error: Option<String>,
+}
+
+/// Base, rank and recipe rewards a lottery reward contract pays a claim,
+/// before the bonus
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LotteryRange {
+    pub reward_contract_name: String,
+    pub lowest: Uint128,
+    pub highest: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ClaimPayout {
+    pub reward_contract_name: String,
+    /// base, rank and recipe rewards after the lottery
+    pub reward: Uint128,
+    pub bonus: Uint128,
+    pub total: Uint128,
+    /// locked in the wallet's vesting instead of paid out
+    pub vested: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Reward {
     pub base_reward: Uint128,
     pub bonus_reward: Uint128,
 }
 
+/// Rewards of the ranks `min_rank..=max_rank`, ranked tokens outside every
+/// tier get no rank reward
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RankTier {
+    pub min_rank: u32,
+    pub max_rank: u32,
+    pub rewards: Vec<TierReward>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TierReward {
+    pub reward_contract_name: String,
+    pub effect: TierEffect,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TierEffect {
+    /// paid as the token's rank reward
+    Amount { amount: Uint128 },
+    /// applied to the base reward
+    Multiply { multiplier: Decimal },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RankTiersResponse {
+    pub tiers: Vec<RankTier>,
+}
+
+/// How a token's rarity score is computed from the trait frequencies
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RarityMethod {
+    /// sum of the information content of the traits, a missing trait type
+    /// counts as a trait shared by the tokens without it
+    Statistical,
+    /// rarity of the token's number of traits
+    TraitCount,
+    /// sum of the inverse trait frequencies, trait types without a weight
+    /// have a weight of one
+    Weighted { weights: Vec<TraitTypeWeight> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TraitTypeWeight {
+    pub trait_type: String,
+    pub weight: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RarityPhase {
+    /// reading the tokens and counting their traits
+    Ingesting,
+    Scoring,
+    /// merging runs of sorted scores, one pass doubles their length
+    Sorting,
+    /// writing the ranks of the sorted scores
+    Ranking,
+    Done,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RarityStatus {
+    /// number of the scan, its stores are kept apart from earlier scans
+    pub scan: u32,
+    pub nft_contract: Addr,
+    pub method: RarityMethod,
+    pub phase: RarityPhase,
+    /// tokens ingested so far
+    pub tokens: u32,
+    /// progress within the current phase
+    pub cursor: u32,
+    /// last token id returned by the collection, paging continues after it
+    pub start_after: Option<String>,
+    /// sorting pass, the scores of pass `n` are sorted in runs of `2^n`
+    pub pass: u32,
+    /// scores taken from the left and the right run at `cursor`
+    pub left: u32,
+    pub right: u32,
+    /// score and rank of the last ranked token, ties carry over a page
+    pub last_score: Option<Uint128>,
+    pub last_rank: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RarityStatusResponse {
+    pub status: Option<RarityStatus>,
+}
+
+/// Raises the base rewards of a collection's tokens as their trait values burn
+/// out, a token gets the highest multiplier of its trait values
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ScarcityBonus {
+    pub supplies: Vec<TraitSupply>,
+    /// multiplier of the last token of its kind
+    pub max_multiplier: Decimal,
+    /// reward contracts whose base reward is multiplied
+    pub reward_contract_names: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TraitSupply {
+    pub trait_type: String,
+    pub value: String,
+    /// tokens minted with the trait value
+    pub total_supply: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TraitScarcity {
+    pub trait_type: String,
+    pub value: String,
+    pub total_supply: u32,
+    pub burned: u32,
+    pub remaining: u32,
+    /// multiplier of the next burned token with the trait value
+    pub multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ScarcityResponse {
+    pub bonus: Option<ScarcityBonus>,
+    pub traits: Vec<TraitScarcity>,
+}
+
+/// Roles held next to the owner, who holds all of them
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// ranks, collections, reward contracts and the rest of the configuration
+    Operator,
+    /// removing and sweeping rewards
+    Treasurer,
+    /// pausing and resuming burns
+    Pauser,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RoleGrant {
+    pub role: Role,
+    pub address: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RolesResponse {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub roles: Vec<RoleGrant>,
+}
+
+/// Rank of a token with the sibling hashes from its leaf up to the rank root
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RankProof {
+    pub rank: Rank,
+    pub proof: Vec<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RankResponse {
+    pub rank: Option<Rank>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RanksResponse {
+    pub ranks: Vec<Rank>,
+    pub total: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RecipeMatchResponse {
+    pub recipe: Option<Recipe>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TraitCheckResponse {
+    pub token_id: String,
+    pub meets_requirements: bool,
+}
+
+/// Remaining number of tokens a wallet may burn under each cap, `None` when
+/// the cap is not set
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnAllowanceResponse {
+    pub wallet_burned: u32,
+    pub max_per_tx: Option<u32>,
+    pub remaining_wallet: Option<u32>,
+    pub remaining_period: Option<u32>,
+    /// when the oldest burn in the window leaves it
+    pub period_resets_at: Option<u64>,
+    pub remaining_total: Option<u32>,
+}
+
+/// Vesting rewards of a wallet for one reward contract
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct RankReward {
-    pub rank_reward: Uint128,
+pub struct VestingBalance {
     pub reward_contract_name: String,
+    /// released so far, including what has already been claimed
+    pub vested: Uint128,
+    /// released but not claimed yet
+    pub claimable: Uint128,
+    /// still locked
+    pub pending: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -0,0 +1,263 @@
+use crate::error::ContractError;
+use crate::migrate::RankV1;
+use crate::msg::{Rank, RankTier, RewardsContractInfo};
+use cosmwasm_std::{StdResult, Storage};
+use secret_toolkit::storage::Keymap;
+
+/// One generation of a collection's ranks, the rank number of every token id
+pub enum RankStore {
+    Ranks(Keymap<'static, String, u32>),
+    /// unsuffixed store of the v1 layout, its rank rewards are not read
+    V1(Keymap<'static, String, RankV1>),
+}
+
+impl RankStore {
+    pub fn get(&self, storage: &dyn Storage, token_id: &str) -> Option<u32> {
+        match self {
+            RankStore::Ranks(store) => store.get(storage, &token_id.to_string()),
+            RankStore::V1(store) => store.get(storage, &token_id.to_string()).map(|x| x.rank),
+        }
+    }
+
+    pub fn insert(&self, storage: &mut dyn Storage, token_id: &str, rank: u32) -> StdResult<()> {
+        match self {
+            RankStore::Ranks(store) => store.insert(storage, &token_id.to_string(), &rank),
+            RankStore::V1(store) => store.insert(
+                storage,
+                &token_id.to_string(),
+                &RankV1 {
+                    token_id: token_id.to_string(),
+                    rank,
+                    rank_rewards: vec![],
+                },
+            ),
+        }
+    }
+
+    pub fn remove(&self, storage: &mut dyn Storage, token_id: &str) -> StdResult<()> {
+        match self {
+            RankStore::Ranks(store) => store.remove(storage, &token_id.to_string()),
+            RankStore::V1(store) => store.remove(storage, &token_id.to_string()),
+        }
+    }
+
+    pub fn contains(&self, storage: &dyn Storage, token_id: &str) -> bool {
+        match self {
+            RankStore::Ranks(store) => store.contains(storage, &token_id.to_string()),
+            RankStore::V1(store) => store.contains(storage, &token_id.to_string()),
+        }
+    }
+
+    pub fn get_len(&self, storage: &dyn Storage) -> StdResult<u32> {
+        match self {
+            RankStore::Ranks(store) => store.get_len(storage),
+            RankStore::V1(store) => store.get_len(storage),
+        }
+    }
+
+    /// Token ids of the first `limit` ranks
+    pub fn token_ids(&self, storage: &dyn Storage, limit: u32) -> StdResult<Vec<String>> {
+        match self {
+            RankStore::Ranks(store) => store.iter_keys(storage)?.take(limit as usize).collect(),
+            RankStore::V1(store) => store.iter_keys(storage)?.take(limit as usize).collect(),
+        }
+    }
+
+    pub fn paging(
+        &self,
+        storage: &dyn Storage,
+        start_page: u32,
+        page_size: u32,
+    ) -> StdResult<Vec<Rank>> {
+        Ok(match self {
+            RankStore::Ranks(store) => store
+                .paging(storage, start_page, page_size)?
+                .into_iter()
+                .map(|(token_id, rank)| Rank { token_id, rank })
+                .collect(),
+            RankStore::V1(store) => store
+                .paging(storage, start_page, page_size)?
+                .into_iter()
+                .map(|(token_id, old)| Rank {
+                    token_id,
+                    rank: old.rank,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Tiers may not overlap, they are checked in order of `min_rank`
+pub fn validate_rank_tiers(
+    tiers: &[RankTier],
+    reward_contracts: &[RewardsContractInfo],
+) -> Result<(), ContractError> {
+    let mut sorted: Vec<&RankTier> = tiers.iter().collect();
+    sorted.sort_by_key(|x| x.min_rank);
+    for (i, tier) in sorted.iter().enumerate() {
+        if tier.min_rank > tier.max_rank {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("rank tier {} starts after its end", tier.min_rank),
+            });
+        }
+        if i > 0 && sorted[i - 1].max_rank >= tier.min_rank {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("rank tier {} overlaps the previous tier", tier.min_rank),
+            });
+        }
+        for tier_reward in tier.rewards.iter() {
+            if !reward_contracts
+                .iter()
+                .any(|x| x.name == tier_reward.reward_contract_name)
+            {
+                return Err(ContractError::RewardNotFound {
+                    name: tier_reward.reward_contract_name.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decodes the packed ranks of `ImportRanks`, per token a `u8` token id
+/// length, the utf8 token id and the rank as big endian `u32`
+pub fn decode_ranks(data: &[u8]) -> Result<Vec<Rank>, ContractError> {
+    let mut reader = Reader { data, position: 0 };
+    let mut ranks: Vec<Rank> = Vec::new();
+    while !reader.is_done() {
+        let id_len = reader.u8()? as usize;
+        let token_id = String::from_utf8(reader.take(id_len)?.to_vec())
+            .map_err(|_| invalid(format!("token id at byte {} is not utf8", reader.position)))?;
+        let rank = u32::from_be_bytes(reader.take(4)?.try_into().unwrap());
+        ranks.push(Rank { token_id, rank });
+    }
+    Ok(ranks)
+}
+
+fn invalid(reason: String) -> ContractError {
+    ContractError::InvalidRankData { reason }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ContractError> {
+        let end = self.position + len;
+        if end > self.data.len() {
+            return Err(invalid(format!("data ends at byte {}", self.data.len())));
+        }
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ContractError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn is_done(&self) -> bool {
+        self.position == self.data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::{RewardStrategy, TierEffect, TierReward};
+    use crate::strategy::{strategy_reward, TokenContext};
+    use crate::testing::test_reward_contract;
+    use cosmwasm_std::{Decimal, Uint128};
+
+    fn reward_contract(name: &str) -> RewardsContractInfo {
+        RewardsContractInfo {
+            burn_type: RewardStrategy::Rank,
+            ..test_reward_contract(name, 0, 0)
+        }
+    }
+
+    #[test]
+    fn packed_ranks_decode() {
+        let mut data: Vec<u8> = vec![2, b'4', b'2'];
+        data.extend_from_slice(&7u32.to_be_bytes());
+        data.extend_from_slice(&[1, b'9']);
+        data.extend_from_slice(&1u32.to_be_bytes());
+
+        let ranks = decode_ranks(&data).unwrap();
+        assert_eq!(
+            ranks,
+            vec![
+                Rank {
+                    token_id: "42".to_string(),
+                    rank: 7,
+                },
+                Rank {
+                    token_id: "9".to_string(),
+                    rank: 1,
+                },
+            ]
+        );
+        assert!(decode_ranks(&data[..data.len() - 1]).is_err());
+        assert_eq!(decode_ranks(&[]).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn rank_tiers_reward_ranked_tokens() {
+        let shill = reward_contract("shill");
+        let mut gold = reward_contract("gold");
+        gold.base_reward = Uint128::from(10u128);
+        let tiers = vec![
+            RankTier {
+                min_rank: 1,
+                max_rank: 10,
+                rewards: vec![
+                    TierReward {
+                        reward_contract_name: "shill".to_string(),
+                        effect: TierEffect::Amount {
+                            amount: Uint128::from(50u128),
+                        },
+                    },
+                    TierReward {
+                        reward_contract_name: "gold".to_string(),
+                        effect: TierEffect::Multiply {
+                            multiplier: Decimal::percent(300),
+                        },
+                    },
+                ],
+            },
+            RankTier {
+                min_rank: 11,
+                max_rank: 100,
+                rewards: vec![],
+            },
+        ];
+        let reward_contracts = vec![shill.clone(), gold.clone()];
+        assert!(validate_rank_tiers(&tiers, &reward_contracts).is_ok());
+        let mut overlapping = tiers.clone();
+        overlapping[1].min_rank = 10;
+        assert!(validate_rank_tiers(&overlapping, &reward_contracts).is_err());
+
+        let reward_for = |rank: u32, reward_contract: &RewardsContractInfo| {
+            let ctx = TokenContext {
+                rank: Some(rank),
+                rank_tiers: &tiers,
+                attributes: &[],
+                burn_position: 0,
+            };
+            let reward = strategy_reward(reward_contract, &ctx);
+            (reward.base_reward, reward.rank_reward)
+        };
+        assert_eq!(
+            reward_for(3, &shill),
+            (Uint128::zero(), Uint128::from(50u128))
+        );
+        assert_eq!(
+            reward_for(3, &gold),
+            (Uint128::from(30u128), Uint128::zero())
+        );
+        assert_eq!(reward_for(50, &shill), (Uint128::zero(), Uint128::zero()));
+        assert_eq!(reward_for(500, &shill), (Uint128::zero(), Uint128::zero()));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::msg::RarityMethod;
+use crate::state::RarityScore;
+use cosmwasm_std::Uint128;
+use std::cmp::Ordering;
+
+/// Fractional bits of the scores, only the order of scores matters
+const FRACTION_BITS: u32 = 32;
+
+/// What is known about a token once the whole collection was ingested
+pub struct ScoreInput<'a> {
+    pub traits: &'a [(String, String)],
+    /// tokens sharing each of `traits`, in the same order
+    pub value_counts: &'a [u32],
+    /// every trait type of the collection with the number of tokens having it
+    pub type_counts: &'a [(String, u32)],
+    /// tokens with as many traits as this one
+    pub size_count: u32,
+    pub total: u32,
+}
+
+/// Rarity score of a token, higher is rarer
+pub fn rarity_score(method: &RarityMethod, input: &ScoreInput) -> u128 {
+    let total = input.total as u128;
+    match method {
+        RarityMethod::Statistical => {
+            // the information content of the token's traits, ordering by it is
+            // ordering by the product of the trait frequencies
+            let mut score: u128 = input
+                .value_counts
+                .iter()
+                .map(|x| log2_fixed(total) - log2_fixed(*x as u128))
+                .sum();
+            for (trait_type, type_count) in input.type_counts.iter() {
+                let has_trait = input.traits.iter().any(|x| &x.0 == trait_type);
+                let missing = total - *type_count as u128;
+                if !has_trait && missing > 0 {
+                    score += log2_fixed(total) - log2_fixed(missing);
+                }
+            }
+            score
+        }
+        RarityMethod::TraitCount => ratio(total, input.size_count as u128),
+        RarityMethod::Weighted { weights } => input
+            .traits
+            .iter()
+            .zip(input.value_counts.iter())
+            .map(|((trait_type, _), count)| {
+                let rarity = Uint128::from(ratio(total, *count as u128));
+                match weights.iter().find(|x| &x.trait_type == trait_type) {
+                    Some(weight) => (rarity * weight.weight).u128(),
+                    None => rarity.u128(),
+                }
+            })
+            .sum(),
+    }
+}
+
+fn ratio(numerator: u128, denominator: u128) -> u128 {
+    (numerator << FRACTION_BITS) / denominator.max(1)
+}
+
+/// log2 of `x` with `FRACTION_BITS` fractional bits, `x` is at least 1
+fn log2_fixed(x: u128) -> u128 {
+    const ONE: u32 = 60;
+    let int = 127 - x.leading_zeros();
+    let mut result = (int as u128) << FRACTION_BITS;
+    // x / 2^int in [1, 2) with 60 fractional bits, squaring it doubles its
+    // log2 and every overflow past 2 is the next bit of the result
+    let mut y = (x << ONE) >> int;
+    for bit in (0..FRACTION_BITS).rev() {
+        y = (y * y) >> ONE;
+        if y >= 2u128 << ONE {
+            y >>= 1;
+            result |= 1 << bit;
+        }
+    }
+    result
+}
+
+/// Whether `a` is ranked before `b`, rarest first and ties ordered by token id
+pub fn ranks_before(a: &RarityScore, b: &RarityScore) -> bool {
+    match b.score.cmp(&a.score) {
+        Ordering::Equal => a.token_id <= b.token_id,
+        order => order == Ordering::Less,
+    }
+}
+
+/// Rank of the sorted score at `index` after the score and rank before it,
+/// equal scores share the rank of the first of them
+pub fn competition_rank(previous: Option<(u128, u32)>, score: u128, index: u32) -> u32 {
+    match previous {
+        Some((previous_score, rank)) if previous_score == score => rank,
+        _ => index + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::TraitTypeWeight;
+    use cosmwasm_std::Decimal;
+
+    fn traits(values: &[(&str, &str)]) -> Vec<(String, String)> {
+        values
+            .iter()
+            .map(|(t, v)| (t.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn rarer_traits_score_higher() {
+        assert_eq!(log2_fixed(1), 0);
+        assert_eq!(log2_fixed(8), 3 << FRACTION_BITS);
+        // log2(3) = 1.58496...
+        assert_eq!(log2_fixed(3) * 100_000 >> FRACTION_BITS, 158_496);
+
+        let type_counts = vec![("Hat".to_string(), 10), ("Eyes".to_string(), 100)];
+        let common = traits(&[("Hat", "Cap"), ("Eyes", "Blue")]);
+        let rare = traits(&[("Hat", "Crown"), ("Eyes", "Blue")]);
+        let bare = traits(&[("Eyes", "Blue")]);
+        let score = |method: &RarityMethod, traits: &[(String, String)], value_counts: &[u32]| {
+            rarity_score(
+                method,
+                &ScoreInput {
+                    traits,
+                    value_counts,
+                    type_counts: &type_counts,
+                    size_count: if traits.len() == 1 { 90 } else { 10 },
+                    total: 100,
+                },
+            )
+        };
+        let statistical = RarityMethod::Statistical;
+        assert!(score(&statistical, &rare, &[1, 100]) > score(&statistical, &common, &[9, 100]));
+        // a missing hat is shared by 90 tokens, less rare than a cap
+        assert!(score(&statistical, &common, &[9, 100]) > score(&statistical, &bare, &[100]));
+        assert!(
+            score(&RarityMethod::TraitCount, &rare, &[1, 100])
+                > score(&RarityMethod::TraitCount, &bare, &[100])
+        );
+
+        let weighted = RarityMethod::Weighted {
+            weights: vec![TraitTypeWeight {
+                trait_type: "Eyes".to_string(),
+                weight: Decimal::zero(),
+            }],
+        };
+        assert_eq!(score(&weighted, &rare, &[1, 100]), 100 << FRACTION_BITS);
+
+        let mut scores = vec![
+            RarityScore {
+                token_id: "b".to_string(),
+                score: 5,
+            },
+            RarityScore {
+                token_id: "c".to_string(),
+                score: 9,
+            },
+            RarityScore {
+                token_id: "a".to_string(),
+                score: 5,
+            },
+        ];
+        scores.sort_by(|a, b| {
+            if ranks_before(a, b) {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        });
+        let order: Vec<&str> = scores.iter().map(|x| x.token_id.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "b"]);
+        assert_eq!(competition_rank(None, 9, 0), 1);
+        assert_eq!(competition_rank(Some((9, 1)), 5, 1), 2);
+        assert_eq!(competition_rank(Some((5, 2)), 5, 2), 2);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::error::ContractError;
+use crate::msg::{Recipe, RecipeIngredient, RewardsContractInfo};
+use secret_toolkit::snip721::Trait;
+
+/// `assign` tries every ingredient for every token, the caps keep a match under
+/// 4^8 picks
+const MAX_RECIPE_INGREDIENTS: usize = 4;
+const MAX_RECIPE_TOKENS: u32 = 8;
+
+pub fn validate_recipes(
+    recipes: &[Recipe],
+    reward_contracts: &[RewardsContractInfo],
+) -> Result<(), ContractError> {
+    for (i, recipe) in recipes.iter().enumerate() {
+        if recipe.name.is_empty() {
+            return Err(ContractError::InvalidConfig {
+                reason: "Recipe name cannot be empty".to_string(),
+            });
+        }
+        if recipes[..i].iter().any(|x| x.name == recipe.name) {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("Duplicate recipe name {}", recipe.name),
+            });
+        }
+        if recipe.ingredients.is_empty() {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("Recipe {} needs at least one ingredient", recipe.name),
+            });
+        }
+        if recipe.ingredients.len() > MAX_RECIPE_INGREDIENTS {
+            return Err(ContractError::InvalidConfig {
+                reason: format!(
+                    "Recipe {} has more than {} ingredients",
+                    recipe.name, MAX_RECIPE_INGREDIENTS
+                ),
+            });
+        }
+        if recipe.ingredients.iter().map(ingredient_count).sum::<u32>() > MAX_RECIPE_TOKENS {
+            return Err(ContractError::InvalidConfig {
+                reason: format!(
+                    "Recipe {} needs more than {} tokens",
+                    recipe.name, MAX_RECIPE_TOKENS
+                ),
+            });
+        }
+        for ingredient in recipe.ingredients.iter() {
+            match ingredient {
+                RecipeIngredient::Matching { rule, count } => {
+                    rule.validate()?;
+                    if *count == 0 {
+                        return Err(ContractError::InvalidConfig {
+                            reason: format!("Recipe {} has an ingredient of 0 tokens", recipe.name),
+                        });
+                    }
+                }
+                RecipeIngredient::SameTrait { count, .. } => {
+                    if *count == 0 {
+                        return Err(ContractError::InvalidConfig {
+                            reason: format!("Recipe {} has an ingredient of 0 tokens", recipe.name),
+                        });
+                    }
+                }
+            }
+        }
+        for reward in recipe.rewards.iter() {
+            if !reward_contracts
+                .iter()
+                .any(|x| x.name == reward.reward_contract_name)
+            {
+                return Err(ContractError::RewardNotFound {
+                    name: reward.reward_contract_name.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// First recipe the burned tokens fulfil, every token has to be used by
+/// exactly one ingredient
+pub fn find_recipe<'a>(recipes: &'a [Recipe], tokens: &[&[Trait]]) -> Option<&'a Recipe> {
+    recipes.iter().find(|x| recipe_matches(x, tokens))
+}
+
+fn recipe_matches(recipe: &Recipe, tokens: &[&[Trait]]) -> bool {
+    let needed: u32 = recipe.ingredients.iter().map(ingredient_count).sum();
+    if needed as usize != tokens.len() {
+        return false;
+    }
+    let mut slots: Vec<Slot> = recipe
+        .ingredients
+        .iter()
+        .map(|x| Slot {
+            ingredient: x,
+            left: ingredient_count(x),
+            value: None,
+        })
+        .collect();
+    assign(&mut slots, tokens)
+}
+
+fn ingredient_count(ingredient: &RecipeIngredient) -> u32 {
+    match ingredient {
+        RecipeIngredient::Matching { count, .. } | RecipeIngredient::SameTrait { count, .. } => {
+            *count
+        }
+    }
+}
+
+struct Slot<'a> {
+    ingredient: &'a RecipeIngredient,
+    left: u32,
+    /// value shared by the tokens of a same trait ingredient
+    value: Option<String>,
+}
+
+/// Tries every ingredient the first token fits and recurses on the rest
+fn assign(slots: &mut [Slot], tokens: &[&[Trait]]) -> bool {
+    let (attributes, rest) = match tokens.split_first() {
+        Some(x) => x,
+        None => return true,
+    };
+    for i in 0..slots.len() {
+        if slots[i].left == 0 {
+            continue;
+        }
+        let previous = slots[i].value.clone();
+        let fits = match slots[i].ingredient {
+            RecipeIngredient::Matching { rule, .. } => rule.matches(attributes),
+            RecipeIngredient::SameTrait { trait_type, .. } => {
+                match attributes
+                    .iter()
+                    .find(|x| x.trait_type.as_deref() == Some(trait_type.as_str()))
+                {
+                    Some(attribute) => match previous.as_ref() {
+                        Some(value) => value == &attribute.value,
+                        None => {
+                            slots[i].value = Some(attribute.value.clone());
+                            true
+                        }
+                    },
+                    None => false,
+                }
+            }
+        };
+        if fits {
+            slots[i].left -= 1;
+            if assign(slots, rest) {
+                return true;
+            }
+            slots[i].left += 1;
+        }
+        slots[i].value = previous;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::TraitRule;
+    use crate::testing::attribute;
+
+    fn part(trait_type: &str) -> RecipeIngredient {
+        RecipeIngredient::Matching {
+            rule: TraitRule::HasTrait {
+                trait_type: trait_type.to_string(),
+            },
+            count: 1,
+        }
+    }
+
+    fn recipe(name: &str, ingredients: Vec<RecipeIngredient>) -> Recipe {
+        Recipe {
+            name: name.to_string(),
+            ingredients,
+            rewards: vec![],
+            replaces_token_rewards: false,
+        }
+    }
+
+    #[test]
+    fn recipes_match_whole_sets() {
+        let recipes = vec![
+            recipe("warrior", vec![part("Head"), part("Body"), part("Weapon")]),
+            recipe(
+                "backgrounds",
+                vec![RecipeIngredient::SameTrait {
+                    trait_type: "Background".to_string(),
+                    count: 3,
+                }],
+            ),
+        ];
+
+        let head = vec![attribute("Head", "Helmet"), attribute("Background", "Red")];
+        let body = vec![attribute("Body", "Armor"), attribute("Background", "Red")];
+        let weapon = vec![attribute("Weapon", "Sword"), attribute("Background", "Red")];
+        let blue = vec![attribute("Background", "Blue")];
+
+        let found = find_recipe(&recipes, &[&weapon, &head, &body]);
+        assert_eq!(found.map(|x| x.name.as_str()), Some("warrior"));
+        assert!(find_recipe(&recipes, &[&head, &body]).is_none());
+        assert!(find_recipe(&recipes, &[&head, &body, &weapon, &weapon]).is_none());
+
+        let found = find_recipe(&recipes[1..], &[&weapon, &head, &body]);
+        assert_eq!(found.map(|x| x.name.as_str()), Some("backgrounds"));
+        assert!(find_recipe(&recipes[1..], &[&head, &body, &blue]).is_none());
+    }
+
+    #[test]
+    fn recipes_are_capped() {
+        let many = recipe("many", vec![part("Head"); MAX_RECIPE_INGREDIENTS + 1]);
+        assert!(matches!(
+            validate_recipes(&[many], &[]),
+            Err(ContractError::InvalidConfig { .. })
+        ));
+        let large = recipe(
+            "large",
+            vec![RecipeIngredient::SameTrait {
+                trait_type: "Background".to_string(),
+                count: MAX_RECIPE_TOKENS + 1,
+            }],
+        );
+        assert!(matches!(
+            validate_recipes(&[large], &[]),
+            Err(ContractError::InvalidConfig { .. })
+        ));
+        let full = recipe("full", vec![part("Head"); MAX_RECIPE_INGREDIENTS]);
+        assert!(validate_recipes(&[full], &[]).is_ok());
+    }
+}
@@ -0,0 +1,65 @@
+use crate::error::ContractError;
+use crate::msg::{Role, RoleGrant};
+use crate::state::State;
+use cosmwasm_std::Addr;
+
+/// The owner holds every role
+pub fn has_role(state: &State, sender: &Addr, role: Role) -> bool {
+    sender == &state.owner
+        || state
+            .roles
+            .iter()
+            .any(|x| x.role == role && &x.address == sender)
+}
+
+pub fn check_role(state: &State, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if !has_role(state, sender, role) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn grant_role(roles: &mut Vec<RoleGrant>, role: Role, address: Addr) {
+    if !roles.iter().any(|x| x.role == role && x.address == address) {
+        roles.push(RoleGrant { role, address });
+    }
+}
+
+pub fn revoke_role(
+    roles: &mut Vec<RoleGrant>,
+    role: Role,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    let position = roles
+        .iter()
+        .position(|x| x.role == role && &x.address == address)
+        .ok_or_else(|| ContractError::RoleNotHeld {
+            role,
+            address: address.to_string(),
+        })?;
+    roles.remove(position);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_state;
+
+    #[test]
+    fn roles_are_checked_per_holder() {
+        let mut state = test_state();
+        let pauser = Addr::unchecked("pauser");
+
+        grant_role(&mut state.roles, Role::Pauser, pauser.clone());
+        grant_role(&mut state.roles, Role::Pauser, pauser.clone());
+        assert_eq!(state.roles.len(), 1);
+        assert!(check_role(&state, &pauser, Role::Pauser).is_ok());
+        assert!(check_role(&state, &pauser, Role::Operator).is_err());
+        assert!(check_role(&state, &state.owner, Role::Treasurer).is_ok());
+
+        revoke_role(&mut state.roles, Role::Pauser, &pauser).unwrap();
+        assert!(check_role(&state, &pauser, Role::Pauser).is_err());
+        assert!(revoke_role(&mut state.roles, Role::Pauser, &pauser).is_err());
+    }
+}
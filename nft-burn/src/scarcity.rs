@@ -0,0 +1,115 @@
+use crate::error::ContractError;
+use crate::msg::{RewardsContractInfo, ScarcityBonus, TraitSupply};
+use cosmwasm_std::Decimal;
+use secret_toolkit::snip721::Trait;
+
+pub fn validate_scarcity_bonus(
+    bonus: &ScarcityBonus,
+    reward_contracts: &[RewardsContractInfo],
+) -> Result<(), ContractError> {
+    if bonus.max_multiplier < Decimal::one() {
+        return Err(ContractError::InvalidConfig {
+            reason: "scarcity multiplier cannot be below one".to_string(),
+        });
+    }
+    for (i, supply) in bonus.supplies.iter().enumerate() {
+        if supply.total_supply == 0 {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("{} {} supply cannot be 0", supply.trait_type, supply.value),
+            });
+        }
+        if bonus.supplies[..i]
+            .iter()
+            .any(|x| x.trait_type == supply.trait_type && x.value == supply.value)
+        {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("duplicate supply of {} {}", supply.trait_type, supply.value),
+            });
+        }
+    }
+    for name in bonus.reward_contract_names.iter() {
+        if !reward_contracts.iter().any(|x| &x.name == name) {
+            return Err(ContractError::RewardNotFound {
+                name: name.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Multiplier of a trait value with `burned` of its `total_supply` tokens
+/// burned, it grows linearly from one to the maximum paid for the last token
+/// of its kind
+pub fn supply_multiplier(max_multiplier: Decimal, total_supply: u32, burned: u32) -> Decimal {
+    if total_supply <= 1 {
+        return max_multiplier;
+    }
+    let scarcity = Decimal::from_ratio(burned.min(total_supply - 1), total_supply - 1);
+    Decimal::one() + (max_multiplier - Decimal::one()) * scarcity
+}
+
+/// Highest multiplier of the token's trait values with a configured supply
+pub fn scarcity_multiplier<F>(bonus: &ScarcityBonus, attributes: &[Trait], burned: F) -> Decimal
+where
+    F: Fn(&TraitSupply) -> u32,
+{
+    bonus
+        .supplies
+        .iter()
+        .filter(|supply| {
+            attributes.iter().any(|x| {
+                x.trait_type.as_deref() == Some(supply.trait_type.as_str())
+                    && x.value == supply.value
+            })
+        })
+        .map(|x| supply_multiplier(bonus.max_multiplier, x.total_supply, burned(x)))
+        .max()
+        .unwrap_or_else(Decimal::one)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::attribute;
+
+    fn supply(trait_type: &str, value: &str, total_supply: u32) -> TraitSupply {
+        TraitSupply {
+            trait_type: trait_type.to_string(),
+            value: value.to_string(),
+            total_supply,
+        }
+    }
+
+    #[test]
+    fn last_of_its_kind_pays_most() {
+        let max = Decimal::percent(300);
+        assert_eq!(supply_multiplier(max, 5, 0), Decimal::one());
+        assert_eq!(supply_multiplier(max, 5, 2), Decimal::percent(200));
+        assert_eq!(supply_multiplier(max, 5, 4), max);
+        assert_eq!(supply_multiplier(max, 5, 9), max);
+        assert_eq!(supply_multiplier(max, 1, 0), max);
+
+        let bonus = ScarcityBonus {
+            supplies: vec![supply("Hat", "Crown", 3), supply("Eyes", "Laser", 5)],
+            max_multiplier: max,
+            reward_contract_names: vec![],
+        };
+        let attributes = vec![attribute("Hat", "Crown"), attribute("Eyes", "Laser")];
+        // one crown of three burned beats two lasers of five
+        let burned = |x: &TraitSupply| if x.value == "Crown" { 1 } else { 2 };
+        assert_eq!(
+            scarcity_multiplier(&bonus, &attributes, burned),
+            Decimal::percent(200)
+        );
+        assert_eq!(
+            scarcity_multiplier(&bonus, &attributes[..0], burned),
+            Decimal::one()
+        );
+
+        let invalid = ScarcityBonus {
+            supplies: vec![supply("Hat", "Crown", 0)],
+            ..bonus
+        };
+        assert!(validate_scarcity_bonus(&invalid, &[]).is_err());
+    }
+}
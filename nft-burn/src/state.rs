@@ -1,8 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::{ContractInfo, History, HistoryFull, Rank, RewardsContractInfo};
-use cosmwasm_std::{Addr};
+use crate::msg::{
+    BurnCaps, ContractInfo, History, HistoryFull, MintReward, NftCollection, RankTier,
+    RarityStatus, Recipe, RewardsContractInfo, RoleGrant, ScarcityBonus, TraitRule,
+    VestingSchedule,
+};
+use cosmwasm_std::{Addr, Binary, Uint128};
 use secret_toolkit::{
     snip721::ViewerInfo,
     storage::{AppendStore, Item, Keymap},
@@ -10,25 +14,137 @@ use secret_toolkit::{
 
 pub static CONFIG_KEY: &[u8] = b"config";
 pub const PREFIX_REVOKED_PERMITS: &str = "revoke";
+/// per wallet history of the v1 layout, read through `HistoryV1`
 pub const HISTORY_KEY: &[u8] = b"history";
+pub const USER_HISTORY_KEY: &[u8] = b"user_history";
+pub const HISTORY_V1_CONTRACT_KEY: &[u8] = b"history_v1_contract";
+/// full history of the v1 layout, read through `HistoryFullV1`
 pub const BURN_HISTORY_KEY: &[u8] = b"burn_history";
+pub const FULL_HISTORY_KEY: &[u8] = b"full_history";
 pub const ADMIN_VIEWING_KEY: &[u8] = b"admin_viewing_key";
 pub const RANK_KEY: &[u8] = b"rank_key";
+pub const RANK_GENERATIONS_KEY: &[u8] = b"rank_generations";
+pub const RANK_ROOTS_KEY: &[u8] = b"rank_roots";
+pub const RANK_TIERS_KEY: &[u8] = b"rank_tiers";
+pub const COLLECTION_BURNS_KEY: &[u8] = b"collection_burns";
+pub const WALLET_BURNS_KEY: &[u8] = b"wallet_burns";
+pub const BONUS_CLOCKS_KEY: &[u8] = b"bonus_clocks";
+pub const VESTING_KEY: &[u8] = b"vesting";
+pub const CONTRACT_VERSION_KEY: &[u8] = b"contract_version";
+pub const RARITY_KEY: &[u8] = b"rarity";
+pub const RARITY_TOKENS_KEY: &[u8] = b"rarity_tokens";
+pub const RARITY_VALUES_KEY: &[u8] = b"rarity_values";
+pub const RARITY_TYPES_KEY: &[u8] = b"rarity_types";
+pub const RARITY_SIZES_KEY: &[u8] = b"rarity_sizes";
+pub const RARITY_SCORES_KEY: &[u8] = b"rarity_scores";
+pub const SCARCITY_KEY: &[u8] = b"scarcity";
+pub const TRAIT_BURNS_KEY: &[u8] = b"trait_burns";
+pub const MINTED_COUNTS_KEY: &[u8] = b"minted_counts";
 
 pub static CONFIG_ITEM: Item<State> = Item::new(CONFIG_KEY);
-pub static HISTORY_STORE: AppendStore<History> = AppendStore::new(HISTORY_KEY);
-pub static BURN_HISTORY_STORE: AppendStore<HistoryFull> = AppendStore::new(BURN_HISTORY_KEY);
+pub static CONTRACT_VERSION_ITEM: Item<ContractVersion> = Item::new(CONTRACT_VERSION_KEY);
+/// suffixed with the wallet address
+pub static HISTORY_STORE: AppendStore<History> = AppendStore::new(USER_HISTORY_KEY);
+pub static BURN_HISTORY_STORE: AppendStore<HistoryFull> = AppendStore::new(FULL_HISTORY_KEY);
 pub static ADMIN_VIEWING_KEY_ITEM: Item<ViewerInfo> = Item::new(ADMIN_VIEWING_KEY);
-pub static RANK_STORE: Keymap<String, Rank> = Keymap::new(RANK_KEY);
+/// suffixed with the collection address and its rank generation, unsuffixed
+/// it holds the ranks of the v1 layout, read through `RankV1`
+pub static RANK_STORE: Keymap<String, u32> = Keymap::new(RANK_KEY);
+/// rank generation of each collection, a finished rarity scan moves its
+/// collection to the generation it ranked into
+pub static RANK_GENERATIONS_STORE: Keymap<Addr, u32> = Keymap::new(RANK_GENERATIONS_KEY);
+/// merkle root of the rank leaves of each collection
+pub static RANK_ROOTS_STORE: Keymap<Addr, Binary> = Keymap::new(RANK_ROOTS_KEY);
+pub static RANK_TIERS_ITEM: Item<Vec<RankTier>> = Item::new(RANK_TIERS_KEY);
+pub static COLLECTION_BURNS_STORE: Keymap<Addr, u32> = Keymap::new(COLLECTION_BURNS_KEY);
+pub static WALLET_BURNS_STORE: Keymap<Addr, WalletBurns> = Keymap::new(WALLET_BURNS_KEY);
+/// bonus accrual start per reward contract name, contracts without an entry
+/// accrue from `State.burn_counter_date`
+pub static BONUS_CLOCKS_STORE: Keymap<String, u64> = Keymap::new(BONUS_CLOCKS_KEY);
+pub static VESTING_STORE: Keymap<Addr, Vec<VestingEntry>> = Keymap::new(VESTING_KEY);
+pub static RARITY_ITEM: Item<RarityStatus> = Item::new(RARITY_KEY);
+/// the stores below are suffixed with the scan number
+pub static RARITY_TOKENS_STORE: AppendStore<RarityToken> = AppendStore::new(RARITY_TOKENS_KEY);
+/// tokens having a trait type and value
+pub static RARITY_VALUES_STORE: Keymap<(String, String), u32> = Keymap::new(RARITY_VALUES_KEY);
+/// tokens having a trait type
+pub static RARITY_TYPES_STORE: Keymap<String, u32> = Keymap::new(RARITY_TYPES_KEY);
+/// tokens having a number of traits
+pub static RARITY_SIZES_STORE: Keymap<u32, u32> = Keymap::new(RARITY_SIZES_KEY);
+/// also suffixed with the sorting pass, the last pass is sorted rarest first
+pub static RARITY_SCORES_STORE: AppendStore<RarityScore> = AppendStore::new(RARITY_SCORES_KEY);
+pub static SCARCITY_STORE: Keymap<Addr, ScarcityBonus> = Keymap::new(SCARCITY_KEY);
+/// burned tokens per trait type and value, suffixed with the collection address
+pub static TRAIT_BURNS_STORE: Keymap<(String, String), u32> = Keymap::new(TRAIT_BURNS_KEY);
+/// tokens minted as rewards per mint contract and token id prefix, numbers the
+/// minted token ids
+pub static MINTED_COUNTS_STORE: Keymap<(Addr, String), u32> = Keymap::new(MINTED_COUNTS_KEY);
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct State {
     pub owner: Addr,
     pub is_active: bool,
-    pub nft_contract: ContractInfo,
+    pub nft_contracts: Vec<NftCollection>,
     pub reward_contracts: Vec<RewardsContractInfo>,
     pub viewing_key: Option<String>,
     pub total_burned_amount: u32,
-    pub trait_restriction: Option<String>,
+    pub trait_rules: Option<TraitRule>,
     pub burn_counter_date: u64,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub caps: BurnCaps,
+    /// seed of the lottery rng, replaced after every transaction rolling it
+    pub prng_seed: Vec<u8>,
+    pub mint_reward: Option<MintReward>,
+    pub recipes: Vec<Recipe>,
+    /// owner proposed by `ProposeOwner`, waiting for `AcceptOwnership`
+    pub pending_owner: Option<Addr>,
+    pub roles: Vec<RoleGrant>,
+}
+
+/// Written by instantiate and every migration, `state_version` is the layout
+/// of the stored state
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ContractVersion {
+    pub contract: String,
+    pub version: String,
+    pub state_version: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct WalletBurns {
+    pub total: u32,
+    /// burns still inside the period cap's window, oldest first
+    pub recent: Vec<TimedBurn>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TimedBurn {
+    pub time: u64,
+    pub count: u32,
+}
+
+/// Rewards of one claim locked for a wallet, the schedule is the one of the
+/// reward contract at the time of the claim
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingEntry {
+    pub reward_contract: ContractInfo,
+    pub denom: Option<String>,
+    pub amount: Uint128,
+    pub claimed: Uint128,
+    pub start: u64,
+    pub schedule: VestingSchedule,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RarityToken {
+    pub token_id: String,
+    /// trait types and values
+    pub traits: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RarityScore {
+    pub token_id: String,
+    pub score: u128,
 }
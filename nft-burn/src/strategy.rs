@@ -0,0 +1,525 @@
+use crate::error::ContractError;
+use crate::lottery::validate_lottery;
+use crate::msg::{
+    BonusCurve, BurnCurve, RankTier, RewardStrategy, RewardsContractInfo, TierEffect, TraitEffect,
+    TraitWeight,
+};
+use crate::vesting::validate_vesting;
+use cosmwasm_std::Uint128;
+use secret_toolkit::snip721::Trait;
+
+/// Everything known about a token when its reward is calculated
+pub struct TokenContext<'a> {
+    pub rank: Option<u32>,
+    pub rank_tiers: &'a [RankTier],
+    pub attributes: &'a [Trait],
+    /// number of tokens burned before this one, including earlier tokens of the same batch
+    pub burn_position: u32,
+}
+
+pub struct StrategyReward {
+    pub base_reward: Uint128,
+    pub rank_reward: Uint128,
+    pub rank: Option<u32>,
+}
+
+impl RewardStrategy {
+    pub fn uses_traits(&self) -> bool {
+        matches!(self, RewardStrategy::TraitWeighted { .. })
+    }
+
+    pub fn uses_rank(&self) -> bool {
+        matches!(self, RewardStrategy::Rank)
+    }
+}
+
+pub fn validate_reward_contracts(contracts: &[RewardsContractInfo]) -> Result<(), ContractError> {
+    for (i, reward_contract) in contracts.iter().enumerate() {
+        if reward_contract.name.is_empty() {
+            return Err(ContractError::InvalidConfig {
+                reason: "Reward contract name cannot be empty".to_string(),
+            });
+        }
+        if contracts[..i]
+            .iter()
+            .any(|x| x.name == reward_contract.name)
+        {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("Duplicate reward contract name {}", reward_contract.name),
+            });
+        }
+        if reward_contract.denom.as_deref() == Some("") {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("{} denom cannot be empty", reward_contract.name),
+            });
+        }
+        if reward_contract.denom.is_some()
+            && contracts[..i]
+                .iter()
+                .any(|x| x.denom == reward_contract.denom)
+        {
+            return Err(ContractError::InvalidConfig {
+                reason: format!(
+                    "{} denom is already paid by another reward contract",
+                    reward_contract.name
+                ),
+            });
+        }
+        validate_strategy(reward_contract)?;
+        validate_bonus_curve(reward_contract)?;
+        validate_vesting(reward_contract)?;
+        validate_lottery(reward_contract)?;
+    }
+    Ok(())
+}
+
+fn validate_strategy(reward_contract: &RewardsContractInfo) -> Result<(), ContractError> {
+    match &reward_contract.burn_type {
+        RewardStrategy::Flat | RewardStrategy::Rank => {}
+        RewardStrategy::TraitWeighted { weights } => {
+            if weights.is_empty() {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("{} trait weights cannot be empty", reward_contract.name),
+                });
+            }
+            if weights.iter().enumerate().any(|(i, x)| {
+                weights[..i]
+                    .iter()
+                    .any(|y| y.trait_type == x.trait_type && y.value == x.value)
+            }) {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("{} has duplicate trait weights", reward_contract.name),
+                });
+            }
+        }
+        RewardStrategy::BurnCountTiers { tiers } => {
+            if tiers.first().map(|x| x.min_burned) != Some(0) {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("{} first tier must start at 0 burns", reward_contract.name),
+                });
+            }
+            if tiers.windows(2).any(|x| x[0].min_burned >= x[1].min_burned) {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("{} tiers must be in increasing order", reward_contract.name),
+                });
+            }
+        }
+        RewardStrategy::Curve { curve } => match curve {
+            BurnCurve::Linear { floor, .. } => {
+                if floor > &reward_contract.base_reward {
+                    return Err(ContractError::InvalidConfig {
+                        reason: format!(
+                            "{} curve floor is above base reward",
+                            reward_contract.name
+                        ),
+                    });
+                }
+            }
+            BurnCurve::Halving { epoch_burns, floor } => {
+                if *epoch_burns == 0 {
+                    return Err(ContractError::InvalidConfig {
+                        reason: format!("{} curve epoch cannot be 0", reward_contract.name),
+                    });
+                }
+                if floor > &reward_contract.base_reward {
+                    return Err(ContractError::InvalidConfig {
+                        reason: format!(
+                            "{} curve floor is above base reward",
+                            reward_contract.name
+                        ),
+                    });
+                }
+            }
+            BurnCurve::Piecewise { points } => {
+                if points.first().map(|x| x.burned) != Some(0) {
+                    return Err(ContractError::InvalidConfig {
+                        reason: format!(
+                            "{} first curve point must be at 0 burns",
+                            reward_contract.name
+                        ),
+                    });
+                }
+                if points.windows(2).any(|x| x[0].burned >= x[1].burned) {
+                    return Err(ContractError::InvalidConfig {
+                        reason: format!(
+                            "{} curve points must be in increasing order",
+                            reward_contract.name
+                        ),
+                    });
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+fn validate_bonus_curve(reward_contract: &RewardsContractInfo) -> Result<(), ContractError> {
+    match &reward_contract.bonus_curve {
+        None | Some(BonusCurve::LinearCapped { .. }) => {}
+        Some(BonusCurve::Step { steps }) => {
+            if steps.is_empty() {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("{} bonus steps cannot be empty", reward_contract.name),
+                });
+            }
+            if steps
+                .windows(2)
+                .any(|x| x[0].after_hours >= x[1].after_hours)
+            {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!(
+                        "{} bonus steps must be in increasing order",
+                        reward_contract.name
+                    ),
+                });
+            }
+        }
+        Some(BonusCurve::Exponential {
+            half_life_hours, ..
+        }) => {
+            if *half_life_hours == 0 {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("{} bonus half life cannot be 0", reward_contract.name),
+                });
+            }
+        }
+        Some(BonusCurve::DutchAuction { period_hours }) => {
+            if *period_hours == 0 {
+                return Err(ContractError::InvalidConfig {
+                    reason: format!("{} bonus period cannot be 0", reward_contract.name),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn strategy_reward(
+    reward_contract: &RewardsContractInfo,
+    ctx: &TokenContext,
+) -> StrategyReward {
+    let mut reward = StrategyReward {
+        base_reward: reward_contract.base_reward,
+        rank_reward: Uint128::zero(),
+        rank: None,
+    };
+
+    match &reward_contract.burn_type {
+        RewardStrategy::Flat => {}
+        RewardStrategy::Rank => {
+            if let Some(rank) = ctx.rank {
+                reward.rank = Some(rank);
+                if let Some(tier_reward) = ctx
+                    .rank_tiers
+                    .iter()
+                    .find(|x| x.min_rank <= rank && rank <= x.max_rank)
+                    .and_then(|x| {
+                        x.rewards
+                            .iter()
+                            .find(|x| x.reward_contract_name == reward_contract.name)
+                    })
+                {
+                    match tier_reward.effect {
+                        TierEffect::Amount { amount } => reward.rank_reward = amount,
+                        TierEffect::Multiply { multiplier } => {
+                            reward.base_reward = reward.base_reward * multiplier
+                        }
+                    }
+                }
+            }
+        }
+        RewardStrategy::TraitWeighted { weights } => {
+            reward.base_reward = trait_weighted_reward(weights, reward.base_reward, ctx.attributes);
+        }
+        RewardStrategy::BurnCountTiers { tiers } => {
+            if let Some(tier) = tiers
+                .iter()
+                .rev()
+                .find(|x| x.min_burned <= ctx.burn_position)
+            {
+                reward.base_reward = tier.reward;
+            }
+        }
+        RewardStrategy::Curve { curve } => {
+            reward.base_reward =
+                curve_reward(curve, reward_contract.base_reward, ctx.burn_position);
+        }
+    }
+
+    reward
+}
+
+fn trait_weighted_reward(weights: &[TraitWeight], base: Uint128, attributes: &[Trait]) -> Uint128 {
+    let mut added = base;
+    let mut multipliers = Vec::new();
+    for weight in weights.iter() {
+        let has_trait = attributes.iter().any(|x| {
+            x.trait_type.as_deref() == Some(weight.trait_type.as_str()) && x.value == weight.value
+        });
+        if has_trait {
+            match weight.effect {
+                TraitEffect::Add { amount } => added += amount,
+                TraitEffect::Multiply { multiplier } => multipliers.push(multiplier),
+            }
+        }
+    }
+    multipliers.iter().fold(added, |acc, x| acc * *x)
+}
+
+fn curve_reward(curve: &BurnCurve, start: Uint128, burn_position: u32) -> Uint128 {
+    match curve {
+        BurnCurve::Linear { decrement, floor } => {
+            let decrease = decrement.saturating_mul(Uint128::from(burn_position));
+            std::cmp::max(start.saturating_sub(decrease), *floor)
+        }
+        BurnCurve::Halving { epoch_burns, floor } => {
+            let halvings = burn_position / epoch_burns;
+            let halved = if halvings >= 128 {
+                Uint128::zero()
+            } else {
+                Uint128::from(start.u128() >> halvings)
+            };
+            std::cmp::max(halved, *floor)
+        }
+        BurnCurve::Piecewise { points } => {
+            let next = points.iter().position(|x| x.burned > burn_position);
+            match next {
+                Some(0) => points[0].reward,
+                None => points.last().map_or(start, |x| x.reward),
+                Some(next) => {
+                    let (from, to) = (&points[next - 1], &points[next]);
+                    let progress = burn_position - from.burned;
+                    let span = to.burned - from.burned;
+                    if to.reward < from.reward {
+                        from.reward - (from.reward - to.reward).multiply_ratio(progress, span)
+                    } else {
+                        from.reward + (to.reward - from.reward).multiply_ratio(progress, span)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bonus of a reward contract after `hours` whole hours without a burn
+pub fn accrued_bonus(reward_contract: &RewardsContractInfo, hours: u64) -> Uint128 {
+    let linear = reward_contract
+        .bonus_hourly
+        .saturating_mul(Uint128::from(hours));
+    match &reward_contract.bonus_curve {
+        None => linear,
+        Some(BonusCurve::LinearCapped { cap }) => std::cmp::min(linear, *cap),
+        Some(BonusCurve::Step { steps }) => steps
+            .iter()
+            .rev()
+            .find(|x| x.after_hours <= hours)
+            .map_or(Uint128::zero(), |x| x.bonus),
+        Some(BonusCurve::Exponential {
+            max,
+            half_life_hours,
+        }) => {
+            let halvings = hours / half_life_hours;
+            if halvings >= 128 {
+                return *max;
+            }
+            // gap left after the full half lives, then closed linearly within the current one
+            let remaining = Uint128::from(max.u128() >> halvings);
+            let closing = remaining.multiply_ratio(hours % half_life_hours, 2 * half_life_hours);
+            *max - remaining + closing
+        }
+        Some(BonusCurve::DutchAuction { period_hours }) => reward_contract
+            .bonus_hourly
+            .saturating_mul(Uint128::from(hours % period_hours)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::{BonusStep, CurvePoint};
+    use crate::testing::{attribute, test_reward_contract};
+    use cosmwasm_std::Decimal;
+
+    fn weight(trait_type: &str, value: &str, effect: TraitEffect) -> TraitWeight {
+        TraitWeight {
+            trait_type: trait_type.to_string(),
+            value: value.to_string(),
+            effect,
+        }
+    }
+
+    fn bonus_contract(bonus_curve: Option<BonusCurve>) -> RewardsContractInfo {
+        RewardsContractInfo {
+            bonus_curve,
+            ..test_reward_contract("reward", 100, 10)
+        }
+    }
+
+    fn bonuses(reward_contract: &RewardsContractInfo, hours: &[u64]) -> Vec<u128> {
+        hours
+            .iter()
+            .map(|x| accrued_bonus(reward_contract, *x).u128())
+            .collect()
+    }
+
+    #[test]
+    fn bonus_curves() {
+        let uncapped = bonus_contract(None);
+        assert_eq!(bonuses(&uncapped, &[0, 5, 100]), vec![0, 50, 1000]);
+
+        let capped = bonus_contract(Some(BonusCurve::LinearCapped {
+            cap: Uint128::from(60u128),
+        }));
+        assert_eq!(bonuses(&capped, &[0, 5, 6, 100]), vec![0, 50, 60, 60]);
+
+        let steps = bonus_contract(Some(BonusCurve::Step {
+            steps: vec![
+                BonusStep {
+                    after_hours: 2,
+                    bonus: Uint128::from(20u128),
+                },
+                BonusStep {
+                    after_hours: 24,
+                    bonus: Uint128::from(500u128),
+                },
+            ],
+        }));
+        assert_eq!(
+            bonuses(&steps, &[1, 2, 23, 24, 1000]),
+            vec![0, 20, 20, 500, 500]
+        );
+
+        let exponential = bonus_contract(Some(BonusCurve::Exponential {
+            max: Uint128::from(1000u128),
+            half_life_hours: 10,
+        }));
+        assert_eq!(
+            bonuses(&exponential, &[0, 5, 10, 15, 20, 30, 10000]),
+            vec![0, 250, 500, 625, 750, 875, 1000]
+        );
+
+        let dutch = bonus_contract(Some(BonusCurve::DutchAuction { period_hours: 24 }));
+        assert_eq!(bonuses(&dutch, &[0, 23, 24, 30]), vec![0, 230, 0, 60]);
+
+        assert!(
+            validate_reward_contracts(&[bonus_contract(Some(BonusCurve::Step { steps: vec![] }))])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn native_denoms_are_unique() {
+        let native = |name: &str, denom: Option<&str>| RewardsContractInfo {
+            name: name.to_string(),
+            denom: denom.map(|x| x.to_string()),
+            ..bonus_contract(None)
+        };
+        assert!(validate_reward_contracts(&[native("a", None), native("b", None)]).is_ok());
+        assert!(validate_reward_contracts(&[
+            native("a", Some("uscrt")),
+            native("b", Some("ustars"))
+        ])
+        .is_ok());
+        assert_eq!(
+            validate_reward_contracts(&[
+                native("a", Some("uscrt")),
+                native("b", None),
+                native("c", Some("uscrt")),
+            ]),
+            Err(ContractError::InvalidConfig {
+                reason: "c denom is already paid by another reward contract".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn trait_weights_add_then_multiply() {
+        let weights = vec![
+            weight(
+                "Tier",
+                "Legendary",
+                TraitEffect::Multiply {
+                    multiplier: Decimal::percent(300),
+                },
+            ),
+            weight(
+                "Background",
+                "Gold",
+                TraitEffect::Add {
+                    amount: Uint128::from(50u128),
+                },
+            ),
+            weight(
+                "Eyes",
+                "Laser",
+                TraitEffect::Multiply {
+                    multiplier: Decimal::percent(150),
+                },
+            ),
+        ];
+        let base = Uint128::from(100u128);
+
+        let plain = vec![attribute("Tier", "Common")];
+        assert_eq!(trait_weighted_reward(&weights, base, &plain), base);
+
+        let legendary = vec![
+            attribute("Tier", "Legendary"),
+            attribute("Background", "Gold"),
+        ];
+        assert_eq!(
+            trait_weighted_reward(&weights, base, &legendary),
+            Uint128::from(450u128)
+        );
+
+        let all = vec![
+            attribute("Tier", "Legendary"),
+            attribute("Background", "Gold"),
+            attribute("Eyes", "Laser"),
+        ];
+        assert_eq!(
+            trait_weighted_reward(&weights, base, &all),
+            Uint128::from(675u128)
+        );
+    }
+
+    #[test]
+    fn burn_curves() {
+        let start = Uint128::from(1000u128);
+        let rewards = |curve: &BurnCurve, positions: &[u32]| -> Vec<u128> {
+            positions
+                .iter()
+                .map(|x| curve_reward(curve, start, *x).u128())
+                .collect()
+        };
+
+        let halving = BurnCurve::Halving {
+            epoch_burns: 10,
+            floor: Uint128::from(200u128),
+        };
+        assert_eq!(
+            rewards(&halving, &[0, 9, 10, 20, 30, u32::MAX]),
+            vec![1000, 1000, 500, 250, 200, 200]
+        );
+
+        let piecewise = BurnCurve::Piecewise {
+            points: vec![
+                CurvePoint {
+                    burned: 0,
+                    reward: Uint128::from(1000u128),
+                },
+                CurvePoint {
+                    burned: 100,
+                    reward: Uint128::from(500u128),
+                },
+                CurvePoint {
+                    burned: 200,
+                    reward: Uint128::from(600u128),
+                },
+            ],
+        };
+        assert_eq!(
+            rewards(&piecewise, &[0, 50, 100, 150, 200, 1000]),
+            vec![1000, 750, 500, 550, 600, 600]
+        );
+    }
+}
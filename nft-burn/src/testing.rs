@@ -0,0 +1,86 @@
+use crate::msg::{BurnCaps, ContractInfo, NftCollection, RewardStrategy, RewardsContractInfo};
+use crate::state::State;
+use cosmwasm_std::{Addr, Uint128};
+use secret_toolkit::snip721::{Extension, Metadata, Trait};
+
+/// active campaign owned by "owner" without any contracts
+pub fn test_state() -> State {
+    State {
+        owner: Addr::unchecked("owner"),
+        is_active: true,
+        nft_contracts: vec![],
+        reward_contracts: vec![],
+        viewing_key: None,
+        total_burned_amount: 0u32,
+        trait_rules: None,
+        burn_counter_date: 0,
+        start_time: None,
+        end_time: None,
+        caps: BurnCaps::default(),
+        prng_seed: vec![],
+        mint_reward: None,
+        recipes: vec![],
+        pending_owner: None,
+        roles: vec![],
+    }
+}
+
+pub fn test_collection(address: &str) -> NftCollection {
+    NftCollection {
+        contract: ContractInfo {
+            code_hash: "".to_string(),
+            address: Addr::unchecked(address),
+            name: "".to_string(),
+        },
+        trait_rules: None,
+        reward_multipliers: vec![],
+        upgrade: None,
+    }
+}
+
+/// flat reward contract at `name` with a pool of 10000
+pub fn test_reward_contract(
+    name: &str,
+    base_reward: u128,
+    bonus_hourly: u128,
+) -> RewardsContractInfo {
+    RewardsContractInfo {
+        code_hash: "".to_string(),
+        address: Addr::unchecked(name),
+        base_reward: Uint128::from(base_reward),
+        bonus_hourly: Uint128::from(bonus_hourly),
+        name: name.to_string(),
+        burn_type: RewardStrategy::Flat,
+        total_rewards: Uint128::from(10000u128),
+        bonus_curve: None,
+        bonus_reset: None,
+        vesting: None,
+        lottery: None,
+        denom: None,
+    }
+}
+
+pub fn attribute(trait_type: &str, value: &str) -> Trait {
+    Trait {
+        display_type: None,
+        trait_type: Some(trait_type.to_string()),
+        value: value.to_string(),
+        max_value: None,
+    }
+}
+
+/// public metadata holding only these trait types and values
+pub fn with_attributes(attributes: Vec<(&str, &str)>) -> Metadata {
+    Metadata {
+        token_uri: None,
+        extension: Some(Extension {
+            attributes: Some(
+                attributes
+                    .into_iter()
+                    .map(|(trait_type, value)| attribute(trait_type, value))
+                    .collect(),
+            ),
+            ..Extension::default()
+        }),
+    }
+}
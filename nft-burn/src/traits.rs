@@ -0,0 +1,100 @@
+use crate::error::ContractError;
+use crate::msg::TraitRule;
+use secret_toolkit::snip721::{Metadata, Trait};
+
+pub fn token_attributes(meta: Option<&Metadata>) -> &[Trait] {
+    meta.and_then(|x| x.extension.as_ref())
+        .and_then(|x| x.attributes.as_deref())
+        .unwrap_or(&[])
+}
+
+fn has_value(attributes: &[Trait], trait_type: &str, value: Option<&str>) -> bool {
+    attributes.iter().any(|x| {
+        x.trait_type.as_deref() == Some(trait_type) && value.map_or(true, |v| x.value == v)
+    })
+}
+
+impl TraitRule {
+    pub fn matches(&self, attributes: &[Trait]) -> bool {
+        match self {
+            TraitRule::HasTrait { trait_type } => has_value(attributes, trait_type, None),
+            TraitRule::Equals { trait_type, value } => {
+                has_value(attributes, trait_type, Some(value))
+            }
+            TraitRule::OneOf { trait_type, values } => values
+                .iter()
+                .any(|value| has_value(attributes, trait_type, Some(value))),
+            TraitRule::And { rules } => rules.iter().all(|x| x.matches(attributes)),
+            TraitRule::Or { rules } => rules.iter().any(|x| x.matches(attributes)),
+            TraitRule::Not { rule } => !rule.matches(attributes),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ContractError> {
+        match self {
+            TraitRule::HasTrait { .. } | TraitRule::Equals { .. } => Ok(()),
+            TraitRule::OneOf { values, .. } => {
+                if values.is_empty() {
+                    return Err(ContractError::InvalidConfig {
+                        reason: "one_of trait rule needs at least one value".to_string(),
+                    });
+                }
+                Ok(())
+            }
+            TraitRule::And { rules } | TraitRule::Or { rules } => {
+                if rules.is_empty() {
+                    return Err(ContractError::InvalidConfig {
+                        reason: "and/or trait rules need at least one rule".to_string(),
+                    });
+                }
+                rules.iter().try_for_each(|x| x.validate())
+            }
+            TraitRule::Not { rule } => rule.validate(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::attribute;
+
+    #[test]
+    fn trait_rules_combine() {
+        let attributes = vec![attribute("Background", "Gold"), attribute("Eyes", "Laser")];
+        let rule = TraitRule::And {
+            rules: vec![
+                TraitRule::Equals {
+                    trait_type: "Background".to_string(),
+                    value: "Gold".to_string(),
+                },
+                TraitRule::OneOf {
+                    trait_type: "Eyes".to_string(),
+                    values: vec!["Laser".to_string(), "Fire".to_string()],
+                },
+                TraitRule::Not {
+                    rule: Box::new(TraitRule::HasTrait {
+                        trait_type: "Hat".to_string(),
+                    }),
+                },
+            ],
+        };
+        assert!(rule.matches(&attributes));
+        assert!(!rule.matches(&[attribute("Background", "Gold")]));
+        assert!(!rule.matches(&[]));
+
+        let either = TraitRule::Or {
+            rules: vec![
+                TraitRule::Equals {
+                    trait_type: "Background".to_string(),
+                    value: "Silver".to_string(),
+                },
+                TraitRule::HasTrait {
+                    trait_type: "Eyes".to_string(),
+                },
+            ],
+        };
+        assert!(either.matches(&attributes));
+        assert!(TraitRule::Or { rules: vec![] }.validate().is_err());
+    }
+}
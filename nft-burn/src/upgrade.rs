@@ -0,0 +1,208 @@
+use crate::contract::BLOCK_SIZE;
+use crate::error::ContractError;
+use crate::msg::{ContractInfo, UpgradeConfig, UpgradeRule};
+use cosmwasm_std::{Addr, QuerierWrapper};
+use secret_toolkit::snip721::{minters_query, Extension, Metadata, Trait};
+use secret_toolkit::utils::Query;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CollectionQueryMsg {
+    ContractConfig {},
+}
+
+impl Query for CollectionQueryMsg {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+}
+
+#[derive(Deserialize)]
+struct ContractConfigResponse {
+    contract_config: ContractConfig,
+}
+
+/// Part of the snip721 contract config an upgrade depends on
+#[derive(Deserialize)]
+struct ContractConfig {
+    minter_may_update_metadata: bool,
+}
+
+pub fn validate_upgrade(upgrade: &UpgradeConfig) -> Result<(), ContractError> {
+    if upgrade.burn_count == 0 {
+        return Err(ContractError::InvalidConfig {
+            reason: "Upgrade burn count cannot be 0".to_string(),
+        });
+    }
+    if upgrade.rules.is_empty() {
+        return Err(ContractError::InvalidConfig {
+            reason: "Upgrade needs at least one rule".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Upgrades set the kept token's metadata as a minter of the collection, so
+/// they are refused before anything is burned when that would fail
+pub fn check_upgrade_rights(
+    querier: QuerierWrapper,
+    contract_address: &Addr,
+    collection: &ContractInfo,
+) -> Result<(), ContractError> {
+    let minters = minters_query(
+        querier,
+        BLOCK_SIZE,
+        collection.code_hash.clone(),
+        collection.address.to_string(),
+    )?;
+    let config: ContractConfigResponse = CollectionQueryMsg::ContractConfig {}.query(
+        querier,
+        collection.code_hash.clone(),
+        collection.address.to_string(),
+    )?;
+    upgrade_rights(
+        &minters.minters,
+        config.contract_config.minter_may_update_metadata,
+        contract_address,
+        collection,
+    )
+}
+
+fn upgrade_rights(
+    minters: &[Addr],
+    minter_may_update_metadata: bool,
+    contract_address: &Addr,
+    collection: &ContractInfo,
+) -> Result<(), ContractError> {
+    if !minters.contains(contract_address) {
+        return Err(ContractError::UpgradeNotMinter {
+            address: collection.address.to_string(),
+        });
+    }
+    if !minter_may_update_metadata {
+        return Err(ContractError::MetadataNotUpdatable {
+            address: collection.address.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Public metadata of the kept token after applying every rule in order
+pub fn upgraded_metadata(
+    rules: &[UpgradeRule],
+    metadata: &Metadata,
+) -> Result<Metadata, ContractError> {
+    let mut upgraded = metadata.clone();
+    let attributes = upgraded
+        .extension
+        .get_or_insert_with(Extension::default)
+        .attributes
+        .get_or_insert_with(Vec::new);
+    for rule in rules.iter() {
+        match rule {
+            UpgradeRule::IncrementTrait {
+                trait_type,
+                amount,
+                max,
+            } => {
+                let attribute = trait_mut(attributes, trait_type, "0");
+                let current: u32 =
+                    attribute
+                        .value
+                        .parse()
+                        .map_err(|_| ContractError::TraitNotNumeric {
+                            trait_type: trait_type.clone(),
+                        })?;
+                if max.map_or(false, |max| current >= max) {
+                    return Err(ContractError::TraitAtMaximum {
+                        trait_type: trait_type.clone(),
+                    });
+                }
+                let next = current.saturating_add(*amount);
+                attribute.value = max.map_or(next, |max| next.min(max)).to_string();
+            }
+            UpgradeRule::SetTrait { trait_type, value } => {
+                trait_mut(attributes, trait_type, value).value = value.clone();
+            }
+        }
+    }
+    Ok(upgraded)
+}
+
+/// Attribute of this trait type, added with `default` when missing
+fn trait_mut<'a>(attributes: &'a mut Vec<Trait>, trait_type: &str, default: &str) -> &'a mut Trait {
+    let position = match attributes
+        .iter()
+        .position(|x| x.trait_type.as_deref() == Some(trait_type))
+    {
+        Some(position) => position,
+        None => {
+            attributes.push(Trait {
+                display_type: None,
+                trait_type: Some(trait_type.to_string()),
+                value: default.to_string(),
+                max_value: None,
+            });
+            attributes.len() - 1
+        }
+    };
+    &mut attributes[position]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::token_attributes;
+
+    #[test]
+    fn upgrade_rules_rewrite_attributes() {
+        let rules = vec![
+            UpgradeRule::IncrementTrait {
+                trait_type: "Level".to_string(),
+                amount: 2,
+                max: Some(3),
+            },
+            UpgradeRule::SetTrait {
+                trait_type: "Aura".to_string(),
+                value: "Gold".to_string(),
+            },
+        ];
+        let plain = Metadata {
+            token_uri: None,
+            extension: None,
+        };
+
+        let once = upgraded_metadata(&rules, &plain).unwrap();
+        let attributes = token_attributes(Some(&once));
+        assert_eq!(attributes[0].value, "2");
+        assert_eq!(attributes[1].value, "Gold");
+
+        let twice = upgraded_metadata(&rules, &once).unwrap();
+        assert_eq!(token_attributes(Some(&twice))[0].value, "3");
+        assert_eq!(token_attributes(Some(&twice)).len(), 2);
+        assert!(upgraded_metadata(&rules, &twice).is_err());
+    }
+
+    #[test]
+    fn upgrades_need_metadata_rights() {
+        let this = Addr::unchecked("burn");
+        let collection = ContractInfo {
+            code_hash: "".to_string(),
+            address: Addr::unchecked("nft"),
+            name: "nft".to_string(),
+        };
+        let minters = vec![Addr::unchecked("admin"), this.clone()];
+        assert!(upgrade_rights(&minters, true, &this, &collection).is_ok());
+        assert_eq!(
+            upgrade_rights(&minters[..1], true, &this, &collection),
+            Err(ContractError::UpgradeNotMinter {
+                address: "nft".to_string()
+            })
+        );
+        assert_eq!(
+            upgrade_rights(&minters, false, &this, &collection),
+            Err(ContractError::MetadataNotUpdatable {
+                address: "nft".to_string()
+            })
+        );
+    }
+}
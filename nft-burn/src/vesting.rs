@@ -0,0 +1,193 @@
+use crate::error::ContractError;
+use crate::msg::{ContractInfo, RewardsContractInfo, VestingBalance};
+use crate::state::VestingEntry;
+use cosmwasm_std::Uint128;
+
+pub fn validate_vesting(reward_contract: &RewardsContractInfo) -> Result<(), ContractError> {
+    if let Some(schedule) = reward_contract.vesting.as_ref() {
+        if schedule.duration_seconds == 0 {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("{} vesting duration cannot be 0", reward_contract.name),
+            });
+        }
+        if schedule.cliff_seconds > schedule.duration_seconds {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("{} vesting cliff is after its end", reward_contract.name),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Part of the entry released at `current_time`, claimed or not
+fn vested_amount(entry: &VestingEntry, current_time: u64) -> Uint128 {
+    let elapsed = current_time.saturating_sub(entry.start);
+    if elapsed < entry.schedule.cliff_seconds {
+        return Uint128::zero();
+    }
+    if elapsed >= entry.schedule.duration_seconds {
+        return entry.amount;
+    }
+    entry
+        .amount
+        .multiply_ratio(elapsed, entry.schedule.duration_seconds)
+}
+
+/// Adds the entry of a claim to the wallet's entries. Entries of the same reward
+/// contract and schedule are merged once fully released, or when they start at
+/// the same time, so a wallet only keeps the ones still vesting apart
+pub fn add_vesting(entries: &mut Vec<VestingEntry>, entry: VestingEntry, current_time: u64) {
+    let mut merged: Vec<VestingEntry> = Vec::new();
+    for old in entries.drain(..).chain(std::iter::once(entry)) {
+        let released = vested_amount(&old, current_time) == old.amount;
+        let same = merged.iter_mut().find(|x| {
+            x.reward_contract == old.reward_contract
+                && x.denom == old.denom
+                && x.schedule == old.schedule
+                && (x.start == old.start || released && vested_amount(x, current_time) == x.amount)
+        });
+        match same {
+            Some(same) => {
+                same.amount += old.amount;
+                same.claimed += old.claimed;
+                same.start = same.start.max(old.start);
+            }
+            None => merged.push(old),
+        }
+    }
+    *entries = merged;
+}
+
+pub struct VestingPayout {
+    pub reward_contract: ContractInfo,
+    pub denom: Option<String>,
+    pub amount: Uint128,
+}
+
+/// Marks everything released so far as claimed and returns the amount to pay
+/// per reward contract, fully claimed entries are dropped
+pub fn claim_vested(entries: &mut Vec<VestingEntry>, current_time: u64) -> Vec<VestingPayout> {
+    let mut payouts: Vec<VestingPayout> = Vec::new();
+    for entry in entries.iter_mut() {
+        let claimable = vested_amount(entry, current_time) - entry.claimed;
+        if claimable.is_zero() {
+            continue;
+        }
+        entry.claimed += claimable;
+        match payouts
+            .iter_mut()
+            .find(|x| x.reward_contract == entry.reward_contract && x.denom == entry.denom)
+        {
+            Some(payout) => payout.amount += claimable,
+            None => payouts.push(VestingPayout {
+                reward_contract: entry.reward_contract.clone(),
+                denom: entry.denom.clone(),
+                amount: claimable,
+            }),
+        }
+    }
+    entries.retain(|x| x.claimed < x.amount);
+    payouts
+}
+
+pub fn vesting_balances(entries: &[VestingEntry], current_time: u64) -> Vec<VestingBalance> {
+    let mut balances: Vec<VestingBalance> = Vec::new();
+    for entry in entries.iter() {
+        let vested = vested_amount(entry, current_time);
+        let balance = match balances
+            .iter_mut()
+            .position(|x| x.reward_contract_name == entry.reward_contract.name)
+        {
+            Some(i) => &mut balances[i],
+            None => {
+                balances.push(VestingBalance {
+                    reward_contract_name: entry.reward_contract.name.clone(),
+                    vested: Uint128::zero(),
+                    claimable: Uint128::zero(),
+                    pending: Uint128::zero(),
+                });
+                balances.last_mut().unwrap()
+            }
+        };
+        balance.vested += vested;
+        balance.claimable += vested - entry.claimed;
+        balance.pending += entry.amount - vested;
+    }
+    balances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::VestingSchedule;
+    use cosmwasm_std::Addr;
+
+    #[test]
+    fn vesting_releases_after_cliff() {
+        let mut entries = vec![VestingEntry {
+            reward_contract: ContractInfo {
+                code_hash: "".to_string(),
+                address: Addr::unchecked("reward"),
+                name: "reward".to_string(),
+            },
+            denom: None,
+            amount: Uint128::from(1000u128),
+            claimed: Uint128::zero(),
+            start: 100,
+            schedule: VestingSchedule {
+                cliff_seconds: 10,
+                duration_seconds: 100,
+            },
+        }];
+
+        assert!(claim_vested(&mut entries, 109).is_empty());
+        let payouts = claim_vested(&mut entries, 150);
+        assert_eq!(payouts[0].amount, Uint128::from(500u128));
+
+        let balances = vesting_balances(&entries, 175);
+        assert_eq!(balances[0].vested, Uint128::from(750u128));
+        assert_eq!(balances[0].claimable, Uint128::from(250u128));
+        assert_eq!(balances[0].pending, Uint128::from(250u128));
+
+        let payouts = claim_vested(&mut entries, 500);
+        assert_eq!(payouts[0].amount, Uint128::from(500u128));
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn vesting_entries_merge() {
+        let entry = |amount: u128, start: u64| VestingEntry {
+            reward_contract: ContractInfo {
+                code_hash: "".to_string(),
+                address: Addr::unchecked("reward"),
+                name: "reward".to_string(),
+            },
+            denom: None,
+            amount: Uint128::from(amount),
+            claimed: Uint128::zero(),
+            start,
+            schedule: VestingSchedule {
+                cliff_seconds: 0,
+                duration_seconds: 100,
+            },
+        };
+        let mut entries: Vec<VestingEntry> = Vec::new();
+        add_vesting(&mut entries, entry(100, 0), 0);
+        add_vesting(&mut entries, entry(100, 0), 0);
+        assert_eq!(entries, vec![entry(200, 0)]);
+
+        claim_vested(&mut entries, 50);
+        add_vesting(&mut entries, entry(100, 60), 60);
+        assert_eq!(entries.len(), 2);
+
+        // both are released by 200, what is left of them is kept in one entry
+        add_vesting(&mut entries, entry(100, 200), 200);
+        let released = VestingEntry {
+            claimed: Uint128::from(100u128),
+            ..entry(300, 60)
+        };
+        assert_eq!(entries, vec![released, entry(100, 200)]);
+        let payouts = claim_vested(&mut entries, 200);
+        assert_eq!(payouts[0].amount, Uint128::from(200u128));
+    }
+}